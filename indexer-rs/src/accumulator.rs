@@ -0,0 +1,73 @@
+// Sequencer inbox accumulator checks.
+// Referencing to: https://github.com/OffchainLabs/nitro-contracts/blob/main/src/bridge/SequencerInbox.sol
+// and `enqueueSequencerMessage` in https://github.com/OffchainLabs/nitro-contracts/blob/main/src/bridge/Bridge.sol
+//
+// The SequencerInbox hashes every batch (40-byte header || payload, or header || 0x50 || blob
+// versioned hashes) and the Bridge folds that hash into `afterAcc = keccak256(beforeAcc || dataHash
+// || delayedAcc)`. Recomputing it from the bytes we fetched proves they are the ones committed to.
+
+use alloy::primitives::{keccak256, B256};
+use thiserror::Error;
+
+use crate::{
+    nitro::batch::BatchHeader,
+    sequencer_inbox::{batch_header, BatchPayload, SequencerBatchDelivered},
+    utils::constants::BLOB_HASHES_HEADER_FLAG,
+};
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum AccumulatorError {
+    #[error("batch {batch}: afterAcc {expected} does not match {computed} recomputed from the batch data")]
+    Mismatch { batch: u64, expected: B256, computed: B256 },
+    #[error("batch {batch}: beforeAcc {found} does not follow the previous batch's afterAcc {expected}")]
+    BrokenChain { batch: u64, expected: B256, found: B256 },
+}
+
+/// Data hash of a batch posted in calldata or a `SequencerBatchData` event; `data` excludes the header.
+pub fn calldata_data_hash(header: &BatchHeader, data: &[u8]) -> B256 {
+    keccak256([&header.to_bytes()[..], data].concat())
+}
+
+/// Data hash of a blob batch: the header, the blob flag and the versioned hashes in order.
+pub fn blob_data_hash(header: &BatchHeader, versioned_hashes: &[B256]) -> B256 {
+    let mut preimage = Vec::with_capacity(BatchHeader::LEN + 1 + 32 * versioned_hashes.len());
+    preimage.extend_from_slice(&header.to_bytes());
+    preimage.push(BLOB_HASHES_HEADER_FLAG);
+    for hash in versioned_hashes {
+        preimage.extend_from_slice(hash.as_slice());
+    }
+    keccak256(preimage)
+}
+
+/// Data hash of `payload`, as the SequencerInbox computed it for `header`.
+pub fn batch_data_hash(header: &BatchHeader, payload: &BatchPayload) -> B256 {
+    match payload {
+        BatchPayload::Data(data) => calldata_data_hash(header, data),
+        BatchPayload::Blobs(hashes) => blob_data_hash(header, hashes),
+        BatchPayload::NoData => calldata_data_hash(header, &[]),
+    }
+}
+
+/// The Bridge's next sequencer inbox accumulator.
+pub fn accumulate(before_acc: B256, data_hash: B256, delayed_acc: B256) -> B256 {
+    keccak256([before_acc.as_slice(), data_hash.as_slice(), delayed_acc.as_slice()].concat())
+}
+
+/// Check that `payload` is the data batch `event` committed to and, when the previous batch's
+/// `afterAcc` is known, that the batch extends it. Returns the batch's `afterAcc`.
+pub fn verify_batch(
+    event: &SequencerBatchDelivered,
+    payload: &BatchPayload,
+    previous_after_acc: Option<B256>,
+) -> Result<B256, AccumulatorError> {
+    let batch = event.batchSequenceNumber.saturating_to();
+    if let Some(expected) = previous_after_acc.filter(|acc| *acc != event.beforeAcc) {
+        return Err(AccumulatorError::BrokenChain { batch, expected, found: event.beforeAcc });
+    }
+    let data_hash = batch_data_hash(&batch_header(event), payload);
+    let computed = accumulate(event.beforeAcc, data_hash, event.delayedAcc);
+    if computed != event.afterAcc {
+        return Err(AccumulatorError::Mismatch { batch, expected: event.afterAcc, computed });
+    }
+    Ok(computed)
+}
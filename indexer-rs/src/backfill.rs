@@ -0,0 +1,89 @@
+// Historical log retrieval over an L1 block range.
+//
+// `eth_getLogs` providers cap the block range and/or the number of results per call, and the
+// caps differ per provider. `LogPager` walks the range in chunks, halving the chunk whenever the
+// node answers with a JSON-RPC error and growing it again after successful pages.
+
+use std::ops::RangeInclusive;
+
+use alloy::{
+    primitives::Address,
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    sol_types::SolEvent,
+    transports::TransportError,
+};
+
+use crate::sequencer_inbox::SequencerBatchDelivered;
+
+/// Initial number of blocks per `eth_getLogs` call.
+pub const DEFAULT_INITIAL_CHUNK: u64 = 2_000;
+/// Upper bound the chunk grows to after successful pages.
+pub const DEFAULT_MAX_CHUNK: u64 = 10_000;
+
+/// Pages `eth_getLogs` over `[from, to]` in adaptive chunks.
+#[derive(Debug, Clone)]
+pub struct LogPager {
+    filter: Filter,
+    next: u64,
+    to: u64,
+    chunk: u64,
+    max_chunk: u64,
+}
+
+impl LogPager {
+    /// `filter` selects addresses and topics; its block range is replaced per page.
+    pub fn new(filter: Filter, from: u64, to: u64) -> Self {
+        Self { filter, next: from, to, chunk: DEFAULT_INITIAL_CHUNK, max_chunk: DEFAULT_MAX_CHUNK }
+    }
+
+    /// `SequencerBatchDelivered` logs of `sequencer_inbox` over `[from, to]`.
+    pub fn batches(sequencer_inbox: Address, from: u64, to: u64) -> Self {
+        let filter = Filter::new()
+            .address(sequencer_inbox)
+            .event_signature(SequencerBatchDelivered::SIGNATURE_HASH);
+        Self::new(filter, from, to)
+    }
+
+    pub fn with_chunk_size(mut self, initial: u64, max: u64) -> Self {
+        self.max_chunk = max.max(1);
+        self.chunk = initial.clamp(1, self.max_chunk);
+        self
+    }
+
+    /// First block not fetched yet.
+    pub fn next_block(&self) -> u64 {
+        self.next
+    }
+
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk
+    }
+
+    /// Fetch the next page: the block range it covers and its logs, or `None` once `to` is passed.
+    pub async fn next_page<P: Provider>(
+        &mut self,
+        provider: &P,
+    ) -> Result<Option<(RangeInclusive<u64>, Vec<Log>)>, TransportError> {
+        if self.next > self.to {
+            return Ok(None);
+        }
+        loop {
+            let end = self.next.saturating_add(self.chunk - 1).min(self.to);
+            let filter = self.filter.clone().from_block(self.next).to_block(end);
+            match provider.get_logs(&filter).await {
+                Ok(logs) => {
+                    let range = self.next..=end;
+                    self.next = end + 1;
+                    self.chunk = (self.chunk * 2).min(self.max_chunk);
+                    return Ok(Some((range, logs)));
+                }
+                // Range or result-size limits come back as JSON-RPC errors; retry with a smaller range.
+                Err(e) if e.as_error_resp().is_some() && self.chunk > 1 => {
+                    self.chunk = (self.chunk / 2).max(1);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// L1 block to beacon slot mapping.
+// Referencing to: https://github.com/ethereum/consensus-specs/blob/dev/specs/phase0/beacon-chain.md (compute_timestamp_at_slot)
+//
+// Post-merge execution blocks are proposed in slots, so a block's timestamp is
+// `genesis_time + slot * SECONDS_PER_SLOT`. Missed slots leave no block behind, which is why the
+// slot cannot be derived from the block number.
+
+use std::{fmt, str::FromStr};
+
+use alloy::{eips::BlockNumberOrTag, providers::Provider, transports::TransportError};
+use thiserror::Error;
+
+pub const SECONDS_PER_SLOT: u64 = 12;
+
+#[derive(Debug, Error)]
+pub enum SlotError {
+    #[error("unknown beacon network `{0}` (expected mainnet, sepolia or holesky)")]
+    UnknownNetwork(String),
+    #[error("timestamp {timestamp} is before the {network} beacon genesis at {genesis_time}")]
+    BeforeGenesis { network: BeaconNetwork, timestamp: u64, genesis_time: u64 },
+    #[error("timestamp {timestamp} is not on a {network} slot boundary")]
+    Unaligned { network: BeaconNetwork, timestamp: u64 },
+    #[error("L1 block {0} not found")]
+    BlockNotFound(u64),
+    #[error(transparent)]
+    Rpc(#[from] TransportError),
+}
+
+/// Beacon chain the L1 blocks belong to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BeaconNetwork {
+    #[default]
+    Mainnet,
+    Sepolia,
+    Holesky,
+}
+
+impl BeaconNetwork {
+    /// `genesis_time` of the network's beacon state.
+    pub const fn genesis_time(self) -> u64 {
+        match self {
+            BeaconNetwork::Mainnet => 1_606_824_023,
+            BeaconNetwork::Sepolia => 1_655_733_600,
+            BeaconNetwork::Holesky => 1_695_902_400,
+        }
+    }
+
+    /// The slot whose block has `timestamp`: `(timestamp - genesis_time) / 12`.
+    pub fn slot_at(self, timestamp: u64) -> Result<u64, SlotError> {
+        let genesis_time = self.genesis_time();
+        let Some(elapsed) = timestamp.checked_sub(genesis_time) else {
+            return Err(SlotError::BeforeGenesis { network: self, timestamp, genesis_time });
+        };
+        if elapsed % SECONDS_PER_SLOT != 0 {
+            return Err(SlotError::Unaligned { network: self, timestamp });
+        }
+        Ok(elapsed / SECONDS_PER_SLOT)
+    }
+
+    /// Timestamp of the block proposed in `slot`.
+    pub const fn timestamp_at(self, slot: u64) -> u64 {
+        self.genesis_time() + slot * SECONDS_PER_SLOT
+    }
+
+    /// The slot of L1 block `block_number`, read from the block's timestamp.
+    pub async fn slot_of_block<P: Provider>(self, provider: &P, block_number: u64) -> Result<u64, SlotError> {
+        let block = provider
+            .get_block_by_number(BlockNumberOrTag::Number(block_number))
+            .await?
+            .ok_or(SlotError::BlockNotFound(block_number))?;
+        self.slot_at(block.header.timestamp)
+    }
+}
+
+impl fmt::Display for BeaconNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BeaconNetwork::Mainnet => "mainnet",
+            BeaconNetwork::Sepolia => "sepolia",
+            BeaconNetwork::Holesky => "holesky",
+        })
+    }
+}
+
+impl FromStr for BeaconNetwork {
+    type Err = SlotError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(BeaconNetwork::Mainnet),
+            "sepolia" => Ok(BeaconNetwork::Sepolia),
+            "holesky" => Ok(BeaconNetwork::Holesky),
+            _ => Err(SlotError::UnknownNetwork(s.to_string())),
+        }
+    }
+}
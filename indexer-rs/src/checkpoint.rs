@@ -0,0 +1,238 @@
+// Durable cursor over the SequencerInbox batches.
+//
+// After a batch has been fully processed the indexer stores its sequence number together with
+// the position of its `SequencerBatchDelivered` log. On restart it resumes from there, and every
+// incoming batch is compared against the cursor to skip duplicates and detect gaps. A batch whose
+// posted data does not match the accumulator is logged as rejected and the cursor moves past it
+// all the same.
+
+use std::{
+    fs,
+    io::Write,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use alloy::{
+    primitives::{Address, B256},
+    providers::Provider,
+    rpc::types::Log,
+    sol_types::SolEvent,
+    transports::TransportError,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{backfill::LogPager, sequencer_inbox::SequencerBatchDelivered};
+
+/// Default location of the checkpoint file.
+pub const DEFAULT_CHECKPOINT_PATH: &str = "checkpoint.json";
+/// Default location of the log of rejected batches.
+pub const DEFAULT_REJECTED_BATCHES_PATH: &str = "rejected_batches.jsonl";
+
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    #[error("checkpoint I/O: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("corrupt checkpoint: {0}")]
+    Corrupt(#[from] serde_json::Error),
+    #[error("batch {0} not found in the SequencerInbox logs")]
+    BatchNotFound(u64),
+    #[error("ABI decoding failed: {0}")]
+    Abi(#[from] alloy::sol_types::Error),
+    #[error(transparent)]
+    Rpc(#[from] TransportError),
+}
+
+/// The last fully processed batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub batch_sequence_number: u64,
+    /// L1 block of the batch's `SequencerBatchDelivered` log.
+    pub l1_block: u64,
+    pub log_index: u64,
+    /// The batch's `afterDelayedMessagesRead`, i.e. the delayed messages read before the next one.
+    pub after_delayed_messages: u64,
+    /// The batch's `afterAcc`, which the next batch's `beforeAcc` must equal.
+    #[serde(default)]
+    pub after_acc: Option<B256>,
+}
+
+/// How an incoming batch relates to the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOrder {
+    /// The batch right after the cursor (or the first batch seen without a cursor).
+    Next,
+    /// At or below the cursor: already processed.
+    Duplicate,
+    /// Batches between the cursor and the incoming one were never seen.
+    Gap(RangeInclusive<u64>),
+}
+
+impl BatchOrder {
+    pub fn of(cursor: Option<&Checkpoint>, batch_sequence_number: u64) -> Self {
+        let Some(cursor) = cursor else {
+            return BatchOrder::Next;
+        };
+        let expected = cursor.batch_sequence_number + 1;
+        if batch_sequence_number < expected {
+            BatchOrder::Duplicate
+        } else if batch_sequence_number == expected {
+            BatchOrder::Next
+        } else {
+            BatchOrder::Gap(expected..=batch_sequence_number - 1)
+        }
+    }
+}
+
+/// A batch whose posted data does not match the Bridge accumulator. It is not indexed, but
+/// the cursor moves past it: left in place, every later log would refetch it as part of a gap.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectedBatch {
+    pub batch_sequence_number: u64,
+    pub l1_block: u64,
+    pub log_index: u64,
+    pub after_delayed_messages: u64,
+    /// The `afterAcc` the Bridge recorded, which the next batch chains from.
+    pub after_acc: B256,
+    pub reason: String,
+}
+
+impl RejectedBatch {
+    pub fn new(event: &SequencerBatchDelivered, l1_block: u64, log_index: u64, reason: impl ToString) -> Self {
+        Self {
+            batch_sequence_number: event.batchSequenceNumber.saturating_to(),
+            l1_block,
+            log_index,
+            after_delayed_messages: event.afterDelayedMessagesRead.saturating_to(),
+            after_acc: event.afterAcc,
+            reason: reason.to_string(),
+        }
+    }
+
+    /// The cursor past the batch.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            batch_sequence_number: self.batch_sequence_number,
+            l1_block: self.l1_block,
+            log_index: self.log_index,
+            after_delayed_messages: self.after_delayed_messages,
+            after_acc: Some(self.after_acc),
+        }
+    }
+}
+
+/// Rejected batches, appended as one JSON line each.
+#[derive(Debug, Clone)]
+pub struct RejectedBatchLog {
+    path: PathBuf,
+}
+
+impl RejectedBatchLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, batch: &RejectedBatch) -> Result<(), CheckpointError> {
+        let mut line = serde_json::to_vec(batch)?;
+        line.push(b'\n');
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(&line)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Every recorded batch, oldest first; empty if nothing was recorded yet.
+    pub fn load(&self) -> Result<Vec<RejectedBatch>, CheckpointError> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        data.lines().filter(|line| !line.trim().is_empty()).map(|line| Ok(serde_json::from_str(line)?)).collect()
+    }
+}
+
+/// A checkpoint stored as JSON in one file, replaced atomically on every save.
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    path: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The stored checkpoint, or `None` on first run.
+    pub fn load(&self) -> Result<Option<Checkpoint>, CheckpointError> {
+        match fs::read(&self.path) {
+            Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write to a temporary file, sync it and rename it over the checkpoint, so a crash leaves
+    /// either the old or the new cursor.
+    pub fn save(&self, checkpoint: &Checkpoint) -> Result<(), CheckpointError> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        {
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(&serde_json::to_vec_pretty(checkpoint)?)?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Remove the checkpoint, so the next run starts as a first run.
+    pub fn clear(&self) -> Result<(), CheckpointError> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The `SequencerBatchDelivered` logs of the `missing` batches, in sequence order, searched for
+/// in L1 blocks `[from, to]`.
+pub async fn fetch_missing_batches<P: Provider>(
+    provider: &P,
+    sequencer_inbox: Address,
+    missing: RangeInclusive<u64>,
+    from: u64,
+    to: u64,
+) -> Result<Vec<(u64, Log)>, CheckpointError> {
+    let mut found = Vec::new();
+    let mut pager = LogPager::batches(sequencer_inbox, from, to);
+    while let Some((_, logs)) = pager.next_page(provider).await? {
+        for log in logs {
+            let event = SequencerBatchDelivered::decode_log(&log.inner)?.data;
+            let seq = event.batchSequenceNumber.saturating_to::<u64>();
+            if missing.contains(&seq) {
+                found.push((seq, log));
+            }
+        }
+    }
+    found.sort_by_key(|(seq, _)| *seq);
+    found.dedup_by_key(|(seq, _)| *seq);
+    let first_missing = (*missing.start()..)
+        .zip(&found)
+        .find(|(want, (seq, _))| want != seq)
+        .map_or(*missing.start() + found.len() as u64, |(want, _)| want);
+    if first_missing <= *missing.end() {
+        return Err(CheckpointError::BatchNotFound(first_missing));
+    }
+    Ok(found)
+}
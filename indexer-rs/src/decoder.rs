@@ -0,0 +1,237 @@
+// Protocol calldata decoders and the registry routing L2 transactions to them.
+//
+// A decoder declares the deployments it knows and the 4-byte selectors it understands. The
+// registry routes a transaction by `(to, selector)` to the decoders registered for that deployment;
+// a transaction to a contract no decoder is registered for (a fork, a new deployment, ...) falls
+// back to every decoder that understands the selector. Protocols are added by registering a
+// decoder, either hand-written over `sol!` bindings or loaded from a JSON ABI at runtime.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use alloy::{
+    dyn_abi::{DynSolValue, JsonAbiExt},
+    json_abi::{ContractObject, Function, JsonAbi},
+    primitives::{Address, Bytes, Selector},
+};
+use thiserror::Error;
+
+use crate::{
+    nitro::tx::L2Transaction,
+    uniswap::{
+        position_manager::{LiquidityAction, PositionDecodeError},
+        router::{RouterDecodeError, SwapIntent},
+        universal_router::{UniversalCommand, UniversalRouterError},
+    },
+};
+
+#[derive(Debug, Error)]
+pub enum DecoderError {
+    #[error("calldata of {0} bytes has no function selector")]
+    NoSelector(usize),
+    #[error("selector {0} is not in the ABI")]
+    UnknownSelector(Bytes),
+    #[error("ABI decoding failed: {0}")]
+    Abi(#[from] alloy::dyn_abi::Error),
+    #[error(transparent)]
+    Router(#[from] RouterDecodeError),
+    #[error(transparent)]
+    PositionManager(#[from] PositionDecodeError),
+    #[error(transparent)]
+    UniversalRouter(#[from] UniversalRouterError),
+}
+
+#[derive(Debug, Error)]
+pub enum AbiLoadError {
+    #[error("ABI I/O: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid JSON ABI: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("JSON of {0} has neither an ABI array nor an `abi` field")]
+    MissingAbi(String),
+}
+
+/// A function call decoded against a JSON ABI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionCall {
+    pub name: String,
+    /// Canonical signature, e.g. `transfer(address,uint256)`.
+    pub signature: String,
+    pub args: Vec<DynSolValue>,
+}
+
+/// What a decoder found in a transaction's calldata.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedCall {
+    Swap(SwapIntent),
+    Liquidity(LiquidityAction),
+    UniversalRouter(UniversalCommand),
+    Function(FunctionCall),
+}
+
+/// Decoder of one protocol's calldata.
+pub trait CalldataDecoder: Send + Sync {
+    /// Short name used in logs.
+    fn name(&self) -> &str;
+
+    /// Deployments whose transactions are routed to this decoder; empty for a decoder only used
+    /// on selector fallback.
+    fn addresses(&self) -> &[Address];
+
+    /// Selectors of the functions this decoder understands.
+    fn selectors(&self) -> Vec<Selector>;
+
+    /// Decode calldata starting with one of [`Self::selectors`].
+    fn decode(&self, input: &[u8]) -> Result<Vec<DecodedCall>, DecoderError>;
+}
+
+/// How a transaction was routed to a decoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// `to` is one of the decoder's deployments.
+    Deployment,
+    /// `to` is not a registered deployment; only the selector matched.
+    Selector,
+}
+
+/// The result of one decoder on a transaction.
+#[derive(Debug)]
+pub struct DecoderOutput<'a> {
+    pub decoder: &'a str,
+    pub route: Route,
+    pub calls: Result<Vec<DecodedCall>, DecoderError>,
+}
+
+/// Decoders indexed by deployment and selector.
+#[derive(Default)]
+pub struct DecoderRegistry {
+    decoders: Vec<Box<dyn CalldataDecoder>>,
+    by_deployment: HashMap<(Address, Selector), Vec<usize>>,
+    by_selector: HashMap<Selector, Vec<usize>>,
+    deployments: HashSet<Address>,
+}
+
+impl DecoderRegistry {
+    pub fn new(decoders: Vec<Box<dyn CalldataDecoder>>) -> Self {
+        let mut registry = Self::default();
+        for decoder in decoders {
+            registry.register(decoder);
+        }
+        registry
+    }
+
+    pub fn register(&mut self, decoder: Box<dyn CalldataDecoder>) {
+        let index = self.decoders.len();
+        let push = |entries: &mut Vec<usize>| {
+            if !entries.contains(&index) {
+                entries.push(index);
+            }
+        };
+        for selector in decoder.selectors() {
+            push(self.by_selector.entry(selector).or_default());
+            for &address in decoder.addresses() {
+                push(self.by_deployment.entry((address, selector)).or_default());
+            }
+        }
+        self.deployments.extend(decoder.addresses());
+        self.decoders.push(decoder);
+    }
+
+    pub fn len(&self) -> usize {
+        self.decoders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decoders.is_empty()
+    }
+
+    /// Decoders a call to `to` with `selector` is routed to, in registration order. A registered
+    /// deployment only reaches its own decoders, even if none of them knows the selector.
+    pub fn route(&self, to: Address, selector: Selector) -> (Route, Vec<&dyn CalldataDecoder>) {
+        let (route, indices) = if self.deployments.contains(&to) {
+            (Route::Deployment, self.by_deployment.get(&(to, selector)))
+        } else {
+            (Route::Selector, self.by_selector.get(&selector))
+        };
+        let decoders = indices.into_iter().flatten().map(|&index| self.decoders[index].as_ref()).collect();
+        (route, decoders)
+    }
+
+    /// Run every decoder the transaction is routed to; empty for contract creations, calldata
+    /// without a selector and calls no decoder understands.
+    pub fn decode(&self, tx: &L2Transaction) -> Vec<DecoderOutput<'_>> {
+        let (Some(to), Some(selector)) = (tx.to, tx.input.get(..4)) else {
+            return Vec::new();
+        };
+        let (route, decoders) = self.route(to, Selector::from_slice(selector));
+        decoders
+            .into_iter()
+            .map(|decoder| DecoderOutput { decoder: decoder.name(), route, calls: decoder.decode(&tx.input) })
+            .collect()
+    }
+}
+
+/// Decoder of the functions of a JSON ABI, the format `sol!` reads `src/abi/*.json` from: either
+/// a bare ABI array or a compiler artifact with an `abi` field.
+pub struct AbiDecoder {
+    name: String,
+    addresses: Vec<Address>,
+    functions: HashMap<Selector, Function>,
+}
+
+impl AbiDecoder {
+    pub fn new(name: impl Into<String>, abi: &JsonAbi, addresses: Vec<Address>) -> Self {
+        let functions = abi.functions().map(|function| (function.selector(), function.clone())).collect();
+        Self { name: name.into(), addresses, functions }
+    }
+
+    pub fn from_json(name: impl Into<String>, json: &str, addresses: Vec<Address>) -> Result<Self, AbiLoadError> {
+        let name = name.into();
+        let abi = match serde_json::from_str::<JsonAbi>(json) {
+            Ok(abi) => abi,
+            Err(_) => serde_json::from_str::<ContractObject>(json)?
+                .abi
+                .ok_or_else(|| AbiLoadError::MissingAbi(name.clone()))?,
+        };
+        Ok(Self::new(name, &abi, addresses))
+    }
+
+    /// Load the ABI at `path`, named after the file stem.
+    pub fn from_file(path: impl AsRef<Path>, addresses: Vec<Address>) -> Result<Self, AbiLoadError> {
+        let path = path.as_ref();
+        let name = path.file_stem().unwrap_or(path.as_os_str()).to_string_lossy().into_owned();
+        Self::from_json(name, &fs::read_to_string(path)?, addresses)
+    }
+}
+
+impl CalldataDecoder for AbiDecoder {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn addresses(&self) -> &[Address] {
+        &self.addresses
+    }
+
+    fn selectors(&self) -> Vec<Selector> {
+        self.functions.keys().copied().collect()
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<DecodedCall>, DecoderError> {
+        let Some(selector) = input.get(..4).map(Selector::from_slice) else {
+            return Err(DecoderError::NoSelector(input.len()));
+        };
+        let function = self
+            .functions
+            .get(&selector)
+            .ok_or_else(|| DecoderError::UnknownSelector(Bytes::copy_from_slice(selector.as_slice())))?;
+        Ok(vec![DecodedCall::Function(FunctionCall {
+            name: function.name.clone(),
+            signature: function.signature(),
+            args: function.abi_decode_input(&input[4..])?,
+        })])
+    }
+}
@@ -0,0 +1,228 @@
+// Retrieval of delayed messages from the Bridge (`MessageDelivered`) and the inbox contracts
+// (`InboxMessageDelivered`, `InboxMessageDeliveredFromOrigin`).
+// Referencing to: https://github.com/OffchainLabs/nitro-contracts/blob/main/src/bridge/Bridge.sol
+// and https://github.com/OffchainLabs/nitro/blob/master/arbnode/delayed.go
+
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+
+use alloy::{
+    consensus::Transaction,
+    primitives::{keccak256, Address, Bytes, B256, U256},
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    sol,
+    sol_types::{SolCall, SolEvent},
+    transports::TransportError,
+};
+use thiserror::Error;
+
+use crate::nitro::delayed::DelayedMessage;
+use crate::sequencer_inbox::{SequencerBatchDelivered, SequencerInbox};
+
+sol! {
+    #[derive(Debug)]
+    event MessageDelivered(
+        uint256 indexed messageIndex,
+        bytes32 indexed beforeInboxAcc,
+        address inbox,
+        uint8 kind,
+        address sender,
+        bytes32 messageDataHash,
+        uint256 baseFeeL1,
+        uint64 timestamp
+    );
+
+    function sendL2MessageFromOrigin(bytes messageData);
+}
+
+/// Default number of L1 blocks searched backwards from a batch for the delayed messages it reads.
+pub const DEFAULT_DELAYED_MESSAGE_LOOKBACK: u64 = 7200;
+
+#[derive(Debug, Error)]
+pub enum DelayedInboxError {
+    #[error("delayed message {0} not found in the Bridge logs")]
+    MissingMessage(u64),
+    #[error("no inbox data event for delayed message {0}")]
+    MissingData(u64),
+    #[error("delayed message {index}: data hash {found} does not match the Bridge's {expected}")]
+    DataHashMismatch { index: u64, expected: B256, found: B256 },
+    #[error("transaction {0} carrying a delayed message not found")]
+    TransactionNotFound(B256),
+    #[error("log is missing its block number or transaction hash")]
+    PendingLog,
+    #[error("ABI decoding failed: {0}")]
+    Abi(#[from] alloy::sol_types::Error),
+    #[error(transparent)]
+    Contract(#[from] alloy::contract::Error),
+    #[error(transparent)]
+    Rpc(#[from] TransportError),
+}
+
+/// Number of delayed messages read before the batch delivered by `event` at `posting_block`,
+/// i.e. the previous batch's `afterDelayedMessagesRead`.
+pub async fn delayed_messages_read_before<P: Provider>(
+    provider: &P,
+    sequencer_inbox: Address,
+    event: &SequencerBatchDelivered,
+    posting_block: u64,
+) -> Result<u64, DelayedInboxError> {
+    if event.batchSequenceNumber.is_zero() {
+        return Ok(0);
+    }
+    // The previous batch may have been posted in the same L1 block.
+    let previous = B256::from(event.batchSequenceNumber - U256::from(1));
+    let filter = Filter::new()
+        .address(sequencer_inbox)
+        .event_signature(SequencerBatchDelivered::SIGNATURE_HASH)
+        .topic1(previous)
+        .from_block(posting_block)
+        .to_block(posting_block);
+    if let Some(log) = provider.get_logs(&filter).await?.first() {
+        let previous = SequencerBatchDelivered::decode_log(&log.inner)?;
+        return Ok(previous.afterDelayedMessagesRead.saturating_to());
+    }
+
+    let read = SequencerInbox::new(sequencer_inbox, provider)
+        .totalDelayedMessagesRead()
+        .block(posting_block.saturating_sub(1).into())
+        .call()
+        .await?;
+    Ok(read.saturating_to())
+}
+
+/// Delayed messages seen so far, keyed by their index in the delayed inbox.
+#[derive(Debug)]
+pub struct DelayedInbox {
+    bridge: Address,
+    lookback: u64,
+    messages: BTreeMap<u64, DelayedMessage>,
+}
+
+impl DelayedInbox {
+    pub fn new(bridge: Address, lookback: u64) -> Self {
+        Self { bridge, lookback, messages: BTreeMap::new() }
+    }
+
+    pub fn bridge(&self) -> Address {
+        self.bridge
+    }
+
+    pub fn get(&self, index: u64) -> Option<&DelayedMessage> {
+        self.messages.get(&index)
+    }
+
+    /// Forget the messages below `index` (already consumed by a batch).
+    pub fn prune_below(&mut self, index: u64) {
+        self.messages = self.messages.split_off(&index);
+    }
+
+    /// Forget the messages delivered in L1 block `block` or later, after those blocks were reorged out.
+    pub fn forget_from_block(&mut self, block: u64) {
+        self.messages.retain(|_, message| message.l1_block < block);
+    }
+
+    /// Return the messages `indexes`, in order, fetching the missing ones from the L1 logs of the
+    /// `lookback` blocks up to `posting_block`.
+    pub async fn resolve<P: Provider>(
+        &mut self,
+        provider: &P,
+        indexes: Range<u64>,
+        posting_block: u64,
+    ) -> Result<Vec<DelayedMessage>, DelayedInboxError> {
+        let missing: Vec<u64> = indexes.clone().filter(|i| !self.messages.contains_key(i)).collect();
+        if !missing.is_empty() {
+            let from_block = posting_block.saturating_sub(self.lookback);
+            self.fetch(provider, &missing, from_block, posting_block).await?;
+        }
+        indexes
+            .map(|i| self.messages.get(&i).cloned().ok_or(DelayedInboxError::MissingMessage(i)))
+            .collect()
+    }
+
+    async fn fetch<P: Provider>(
+        &mut self,
+        provider: &P,
+        indexes: &[u64],
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<(), DelayedInboxError> {
+        let topics: Vec<B256> = indexes.iter().map(|&i| B256::from(U256::from(i))).collect();
+        let filter = Filter::new()
+            .address(self.bridge)
+            .event_signature(MessageDelivered::SIGNATURE_HASH)
+            .topic1(topics.clone())
+            .from_block(from_block)
+            .to_block(to_block);
+        let delivered = provider.get_logs(&filter).await?;
+        if delivered.is_empty() {
+            return Ok(());
+        }
+
+        let mut bridge_events = Vec::with_capacity(delivered.len());
+        for log in &delivered {
+            let block = log.block_number.ok_or(DelayedInboxError::PendingLog)?;
+            bridge_events.push((block, MessageDelivered::decode_log(&log.inner)?.data));
+        }
+        let inboxes: HashSet<Address> = bridge_events.iter().map(|(_, e)| e.inbox).collect();
+        let first_block = bridge_events.iter().map(|(b, _)| *b).min().unwrap_or(from_block);
+        let last_block = bridge_events.iter().map(|(b, _)| *b).max().unwrap_or(to_block);
+
+        let filter = Filter::new()
+            .address(inboxes.into_iter().collect::<Vec<_>>())
+            .event_signature(vec![
+                SequencerInbox::InboxMessageDelivered::SIGNATURE_HASH,
+                SequencerInbox::InboxMessageDeliveredFromOrigin::SIGNATURE_HASH,
+            ])
+            .topic1(topics)
+            .from_block(first_block)
+            .to_block(last_block);
+        let data_logs = provider.get_logs(&filter).await?;
+
+        for (block, event) in bridge_events {
+            let index: u64 = event.messageIndex.saturating_to();
+            let data = message_data(provider, &data_logs, event.inbox, event.messageIndex).await?;
+            let found = keccak256(&data);
+            if found != event.messageDataHash {
+                return Err(DelayedInboxError::DataHashMismatch { index, expected: event.messageDataHash, found });
+            }
+            self.messages.insert(
+                index,
+                DelayedMessage {
+                    index,
+                    kind: event.kind,
+                    sender: event.sender,
+                    l1_block: block,
+                    timestamp: event.timestamp,
+                    base_fee_l1: event.baseFeeL1,
+                    data,
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Data of delayed message `index`, from the inbox event or, for `FromOrigin` messages,
+/// from the calldata of the transaction that emitted it.
+async fn message_data<P: Provider>(
+    provider: &P,
+    logs: &[Log],
+    inbox: Address,
+    index: U256,
+) -> Result<Bytes, DelayedInboxError> {
+    let log = logs
+        .iter()
+        .find(|log| log.address() == inbox && log.topics().get(1) == Some(&B256::from(index)))
+        .ok_or(DelayedInboxError::MissingData(index.saturating_to()))?;
+
+    if log.topics().first() == Some(&SequencerInbox::InboxMessageDelivered::SIGNATURE_HASH) {
+        return Ok(SequencerInbox::InboxMessageDelivered::decode_log(&log.inner)?.data.data.clone());
+    }
+    let tx_hash = log.transaction_hash.ok_or(DelayedInboxError::PendingLog)?;
+    let tx = provider
+        .get_transaction_by_hash(tx_hash)
+        .await?
+        .ok_or(DelayedInboxError::TransactionNotFound(tx_hash))?;
+    Ok(sendL2MessageFromOriginCall::abi_decode(tx.input())?.messageData)
+}
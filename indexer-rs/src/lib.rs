@@ -0,0 +1,13 @@
+pub mod accumulator;
+pub mod arbitrum;
+pub mod backfill;
+pub mod beacon;
+pub mod checkpoint;
+pub mod decoder;
+pub mod delayed_inbox;
+pub mod nitro;
+pub mod provider;
+pub mod reorg;
+pub mod sequencer_inbox;
+pub mod uniswap;
+pub mod utils;
@@ -0,0 +1,512 @@
+// Typed model of a Nitro sequencer batch and its strict decoder.
+// Referencing to: https://github.com/OffchainLabs/nitro/blob/master/arbstate/inbox.go
+// and https://github.com/OffchainLabs/nitro/blob/master/arbos/parse_l2.go
+//
+// A sequencer message is `header (40 bytes) || payload`. For blob batches the header is not
+// posted in the blobs: it is rebuilt from the `SequencerBatchDelivered` event and the payload
+// is the data decoded from the blobs.
+//
+// The first payload byte is a set of flags (`arbstate/daprovider/util.go`):
+//   0x00 brotli, 0x80 DAS certificate (0x08 tree DAS), 0x50 blob hashes,
+//   0x40 L1-authenticated (set by the SequencerInbox), 0x20 zero-heavy encoding.
+
+use std::fmt;
+use std::io::Read;
+
+use alloy::{
+    primitives::{Address, Bytes, B256, U256},
+    rlp::{Decodable, Header},
+};
+use thiserror::Error;
+
+use crate::utils::constants::*;
+
+/// Maximum size of a decompressed sequencer payload (16 MiB).
+pub const MAX_DECOMPRESSED_LEN: usize = 16 * 1024 * 1024;
+/// Maximum number of segments Nitro reads from one sequencer message.
+pub const MAX_SEGMENTS_PER_SEQUENCER_MESSAGE: usize = 100 * 1024;
+/// Maximum nesting depth of `L2_MESSAGE_KIND_BATCH` messages.
+pub const MAX_L2_MESSAGE_BATCH_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum BatchDecodeError {
+    #[error("sequencer message too short for the 40 byte header: {0} bytes")]
+    HeaderTooShort(usize),
+    #[error("empty sequencer payload")]
+    EmptyPayload,
+    #[error("DAS certificate batch (header byte {0:#04x}) must be resolved through a DataAvailabilityReader")]
+    DasCertificate(u8),
+    #[error("blob-hashes batch: its payload lives in {0} blob(s)")]
+    BlobHashes(usize),
+    #[error("blob-hashes payload of {0} bytes is not a list of 32-byte hashes")]
+    MalformedBlobHashes(usize),
+    #[error("zero-heavy encoded payloads are not supported (header byte {0:#04x})")]
+    ZeroheavyNotSupported(u8),
+    #[error("unsupported L1-authenticated header byte {0:#04x}: the SequencerInbox accepted a format this decoder does not know")]
+    UnknownAuthenticatedHeaderByte(u8),
+    #[error("unknown sequencer payload header byte {0:#04x}")]
+    UnknownHeaderByte(u8),
+    #[error("brotli decompression failed: {0}")]
+    Decompression(String),
+    #[error("decompressed data exceeds {limit} bytes")]
+    DecompressedTooLarge { limit: usize },
+    #[error("segment stream offset {offset}: {reason}")]
+    SegmentRlp { offset: usize, reason: String },
+    #[error("more than {MAX_SEGMENTS_PER_SEQUENCER_MESSAGE} segments in sequencer message")]
+    TooManySegments,
+    #[error("segment {index} at offset {offset}: invalid advance value: {reason}")]
+    InvalidAdvance { index: usize, offset: usize, reason: String },
+    #[error("segment {index} at offset {offset}: {source}")]
+    Segment { index: usize, offset: usize, source: L2MessageError },
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum L2MessageError {
+    #[error("empty L2 message")]
+    Empty,
+    #[error("L2 message exceeds {MAX_L2_MESSAGE_SIZE} bytes ({0} bytes)")]
+    TooLarge(usize),
+    #[error("brotli decompression failed: {0}")]
+    Decompression(String),
+    #[error("L2 message batches have a max depth of {MAX_L2_MESSAGE_BATCH_DEPTH}")]
+    BatchTooDeep,
+    #[error("nested batch offset {offset}: {reason}")]
+    BatchFrame { offset: usize, reason: String },
+    #[error("unsigned tx truncated: need {needed} bytes, have {available}")]
+    UnsignedTxTruncated { needed: usize, available: usize },
+}
+
+/// The 40-byte header the SequencerInbox prepends to every batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchHeader {
+    pub min_timestamp: u64,
+    pub max_timestamp: u64,
+    pub min_l1_block: u64,
+    pub max_l1_block: u64,
+    pub after_delayed_messages: u64,
+}
+
+impl BatchHeader {
+    pub const LEN: usize = 40;
+
+    /// Parse the big-endian header and return it with the payload that follows.
+    pub fn parse(data: &[u8]) -> Result<(Self, &[u8]), BatchDecodeError> {
+        if data.len() < Self::LEN {
+            return Err(BatchDecodeError::HeaderTooShort(data.len()));
+        }
+        let word = |i: usize| u64::from_be_bytes(data[i * 8..(i + 1) * 8].try_into().unwrap());
+        let header = BatchHeader {
+            min_timestamp: word(0),
+            max_timestamp: word(1),
+            min_l1_block: word(2),
+            max_l1_block: word(3),
+            after_delayed_messages: word(4),
+        };
+        Ok((header, &data[Self::LEN..]))
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut out = [0u8; Self::LEN];
+        for (i, v) in [
+            self.min_timestamp,
+            self.max_timestamp,
+            self.min_l1_block,
+            self.max_l1_block,
+            self.after_delayed_messages,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            out[i * 8..(i + 1) * 8].copy_from_slice(&v.to_be_bytes());
+        }
+        out
+    }
+}
+
+/// The flags byte that opens a sequencer payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderFlags(pub u8);
+
+impl HeaderFlags {
+    pub const BROTLI: Self = Self(BROTLI_MESSAGE_HEADER_BYTE);
+    pub const BLOB_HASHES: Self = Self(BLOB_HASHES_HEADER_FLAG);
+
+    /// Every bit Nitro assigns a meaning to.
+    pub const KNOWN_BITS: u8 = DASMESSAGE_HEADER_FLAG
+        | TREE_DAS_MESSAGE_HEADER_FLAG
+        | L1_AUTHENTICATED_MESSAGE_HEADER_FLAG
+        | ZEROHEAVY_MESSAGE_HEADER_FLAG
+        | BLOB_HASHES_HEADER_FLAG
+        | BROTLI_MESSAGE_HEADER_BYTE;
+
+    pub fn is_brotli(self) -> bool {
+        self.0 == BROTLI_MESSAGE_HEADER_BYTE
+    }
+
+    pub fn is_das(self) -> bool {
+        self.0 & DASMESSAGE_HEADER_FLAG != 0
+    }
+
+    pub fn is_tree_das(self) -> bool {
+        self.0 & TREE_DAS_MESSAGE_HEADER_FLAG != 0
+    }
+
+    pub fn is_l1_authenticated(self) -> bool {
+        self.0 & L1_AUTHENTICATED_MESSAGE_HEADER_FLAG != 0
+    }
+
+    pub fn is_blob_hashes(self) -> bool {
+        self.0 & BLOB_HASHES_HEADER_FLAG == BLOB_HASHES_HEADER_FLAG
+    }
+
+    pub fn is_zeroheavy(self) -> bool {
+        self.0 & ZEROHEAVY_MESSAGE_HEADER_FLAG != 0
+    }
+
+    pub fn is_known(self) -> bool {
+        self.0 & !Self::KNOWN_BITS == 0
+    }
+
+    /// Names of the flags set, e.g. `["das", "tree-das"]`.
+    pub fn names(self) -> Vec<&'static str> {
+        if self.is_brotli() {
+            return vec!["brotli"];
+        }
+        let mut names = Vec::new();
+        if self.is_das() {
+            names.push("das");
+        }
+        if self.is_tree_das() {
+            names.push("tree-das");
+        }
+        if self.is_blob_hashes() {
+            names.push("blob-hashes");
+        } else if self.is_l1_authenticated() {
+            names.push("l1-authenticated");
+        }
+        if self.is_zeroheavy() {
+            names.push("zero-heavy");
+        }
+        if !self.is_known() {
+            names.push("unknown");
+        }
+        names
+    }
+}
+
+impl fmt::Display for HeaderFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#04x} ({})", self.0, self.names().join("|"))
+    }
+}
+
+/// Versioned hashes listed after the blob-hashes flag byte.
+pub fn parse_blob_hashes(payload: &[u8]) -> Result<Vec<B256>, BatchDecodeError> {
+    let hashes = payload.get(1..).unwrap_or_default();
+    if hashes.len() % 32 != 0 {
+        return Err(BatchDecodeError::MalformedBlobHashes(hashes.len()));
+    }
+    Ok(hashes.chunks_exact(32).map(B256::from_slice).collect())
+}
+
+/// A decoded sequencer batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequencerBatch {
+    pub header: BatchHeader,
+    /// Flags of the payload as posted to L1 (before any DAS resolution), or of the payload
+    /// recovered from the blobs of a blob batch; `None` when the batch has no payload.
+    pub flags: Option<HeaderFlags>,
+    pub segments: Vec<Segment>,
+}
+
+/// One segment of the brotli-compressed segment stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// Kinds 0 (plain) and 1 (brotli); `compressed` records which one was used.
+    L2Message { compressed: bool, message: L2Message },
+    /// Kind 2: consume the next message of the delayed inbox.
+    DelayedMessages,
+    /// Kind 3: advance the timestamp of the following messages.
+    AdvanceTimestamp(u64),
+    /// Kind 4: advance the L1 block number of the following messages.
+    AdvanceL1BlockNumber(u64),
+    /// Any other kind; Nitro skips these.
+    Unknown { kind: u8, payload: Bytes },
+}
+
+/// A decoded L2 message (`arbos/parse_l2.go`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum L2Message {
+    UnsignedUserTx(UnsignedTx),
+    ContractTx(UnsignedTx),
+    NonmutatingCall(Bytes),
+    Batch(Vec<L2Message>),
+    /// A signed Ethereum transaction in its binary (typed envelope or legacy RLP) encoding.
+    SignedTx(Bytes),
+    Heartbeat,
+    SignedCompressedTx(Bytes),
+    Unknown { kind: u8, payload: Bytes },
+}
+
+/// Body of `L2_MESSAGE_KIND_UNSIGNED_USER_TX` / `L2_MESSAGE_KIND_CONTRACT_TX` messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsignedTx {
+    pub gas_limit: U256,
+    pub max_fee_per_gas: U256,
+    /// Only present for unsigned user transactions.
+    pub nonce: Option<U256>,
+    /// `None` for contract creation (zero address).
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+impl SequencerBatch {
+    /// Decode a full sequencer message (`header || payload`), as found in calldata or events.
+    pub fn decode_sequencer_message(data: &[u8]) -> Result<Self, BatchDecodeError> {
+        let (header, payload) = BatchHeader::parse(data)?;
+        Self::decode(header, payload)
+    }
+
+    /// A batch without payload (`BatchDataLocation.NoData`); it only reads delayed messages.
+    pub fn empty(header: BatchHeader) -> Self {
+        SequencerBatch { header, flags: None, segments: Vec::new() }
+    }
+
+    /// Decode the payload that follows `header` (for blob batches: the data decoded from the blobs).
+    ///
+    /// DAS certificates and blob-hash lists are rejected with a dedicated error: their data must
+    /// first be fetched (`nitro::das`, `nitro::batch_blobs`).
+    pub fn decode(header: BatchHeader, payload: &[u8]) -> Result<Self, BatchDecodeError> {
+        let Some(&flag) = payload.first() else {
+            return Err(BatchDecodeError::EmptyPayload);
+        };
+        let flags = HeaderFlags(flag);
+        if flags.is_l1_authenticated() && !flags.is_known() {
+            return Err(BatchDecodeError::UnknownAuthenticatedHeaderByte(flag));
+        }
+        if flags.is_das() {
+            return Err(BatchDecodeError::DasCertificate(flag));
+        }
+        if flags.is_blob_hashes() {
+            return Err(BatchDecodeError::BlobHashes(parse_blob_hashes(payload)?.len()));
+        }
+        if flags.is_zeroheavy() {
+            return Err(BatchDecodeError::ZeroheavyNotSupported(flag));
+        }
+        if !flags.is_brotli() {
+            return Err(BatchDecodeError::UnknownHeaderByte(flag));
+        }
+
+        let decompressed = brotli_decompress(&payload[1..], MAX_DECOMPRESSED_LEN)
+            .map_err(|e| match e {
+                DecompressError::TooLarge => BatchDecodeError::DecompressedTooLarge { limit: MAX_DECOMPRESSED_LEN },
+                DecompressError::Invalid(reason) => BatchDecodeError::Decompression(reason),
+            })?;
+        let segments = decode_segments(&decompressed)?;
+        Ok(SequencerBatch { header, flags: Some(flags), segments })
+    }
+
+    /// Every L2 message of the batch, in order (nested batches are not flattened).
+    pub fn l2_messages(&self) -> impl Iterator<Item = &L2Message> {
+        self.segments.iter().filter_map(|s| match s {
+            Segment::L2Message { message, .. } => Some(message),
+            _ => None,
+        })
+    }
+
+    /// Raw bytes of every signed transaction in the batch, flattening nested batches.
+    pub fn signed_txs(&self) -> Vec<&Bytes> {
+        let mut out = Vec::new();
+        for message in self.l2_messages() {
+            message.collect_signed_txs(&mut out);
+        }
+        out
+    }
+
+    /// Number of delayed messages this batch consumes.
+    pub fn delayed_message_count(&self) -> usize {
+        self.segments.iter().filter(|s| matches!(s, Segment::DelayedMessages)).count()
+    }
+}
+
+impl L2Message {
+    fn collect_signed_txs<'a>(&'a self, out: &mut Vec<&'a Bytes>) {
+        match self {
+            L2Message::SignedTx(raw) => out.push(raw),
+            L2Message::Batch(inner) => inner.iter().for_each(|m| m.collect_signed_txs(out)),
+            _ => {}
+        }
+    }
+}
+
+/// Decode the decompressed segment stream: a concatenation of RLP byte strings,
+/// each holding `kind (1 byte) || payload`. Empty segments are counted but skipped, like Nitro does.
+pub fn decode_segments(stream: &[u8]) -> Result<Vec<Segment>, BatchDecodeError> {
+    let mut segments = Vec::new();
+    let mut cursor = stream;
+    let mut index = 0usize;
+    while !cursor.is_empty() {
+        let offset = stream.len() - cursor.len();
+        let raw = take_rlp_string(&mut cursor)
+            .map_err(|reason| BatchDecodeError::SegmentRlp { offset, reason })?;
+        // Empty segments count towards the limit and the segment indices, as in Nitro.
+        if index >= MAX_SEGMENTS_PER_SEQUENCER_MESSAGE {
+            return Err(BatchDecodeError::TooManySegments);
+        }
+        if !raw.is_empty() {
+            segments.push(decode_segment(index, offset, raw)?);
+        }
+        index += 1;
+    }
+    Ok(segments)
+}
+
+/// Decode one non-empty segment (`kind || payload`); `index` and `offset` (of the segment in the
+/// decompressed stream) are only used for error reporting.
+pub fn decode_segment(index: usize, offset: usize, raw: &[u8]) -> Result<Segment, BatchDecodeError> {
+    let (kind, payload) = (raw[0], &raw[1..]);
+    let segment = match kind {
+        BATCH_SEGMENT_KIND_L2_MESSAGE => Segment::L2Message {
+            compressed: false,
+            message: decode_l2_message(payload).map_err(|source| BatchDecodeError::Segment { index, offset, source })?,
+        },
+        BATCH_SEGMENT_KIND_L2_MESSAGE_BROTLI => {
+            let decompressed = brotli_decompress(payload, MAX_L2_MESSAGE_SIZE as usize).map_err(|e| {
+                let source = match e {
+                    DecompressError::TooLarge => L2MessageError::TooLarge(MAX_L2_MESSAGE_SIZE as usize + 1),
+                    DecompressError::Invalid(reason) => L2MessageError::Decompression(reason),
+                };
+                BatchDecodeError::Segment { index, offset, source }
+            })?;
+            Segment::L2Message {
+                compressed: true,
+                message: decode_l2_message(&decompressed)
+                    .map_err(|source| BatchDecodeError::Segment { index, offset, source })?,
+            }
+        }
+        BATCH_SEGMENT_KIND_DELAYED_MESSAGES => Segment::DelayedMessages,
+        BATCH_SEGMENT_KIND_ADVANCE_TIMESTAMP | BATCH_SEGMENT_KIND_ADVANCE_L1_BLOCK_NUMBER => {
+            let advance = u64::decode(&mut &payload[..])
+                .map_err(|e| BatchDecodeError::InvalidAdvance { index, offset, reason: e.to_string() })?;
+            if kind == BATCH_SEGMENT_KIND_ADVANCE_TIMESTAMP {
+                Segment::AdvanceTimestamp(advance)
+            } else {
+                Segment::AdvanceL1BlockNumber(advance)
+            }
+        }
+        _ => Segment::Unknown { kind, payload: Bytes::copy_from_slice(payload) },
+    };
+    Ok(segment)
+}
+
+/// Decode an L2 message (`kind (1 byte) || body`).
+pub fn decode_l2_message(msg: &[u8]) -> Result<L2Message, L2MessageError> {
+    decode_l2_message_at_depth(msg, 0)
+}
+
+fn decode_l2_message_at_depth(msg: &[u8], depth: usize) -> Result<L2Message, L2MessageError> {
+    let Some((&kind, body)) = msg.split_first() else {
+        return Err(L2MessageError::Empty);
+    };
+    if msg.len() > MAX_L2_MESSAGE_SIZE as usize {
+        return Err(L2MessageError::TooLarge(msg.len()));
+    }
+
+    let message = match kind {
+        L2_MESSAGE_KIND_UNSIGNED_USER_TX => L2Message::UnsignedUserTx(decode_unsigned_tx(body, true)?),
+        L2_MESSAGE_KIND_CONTRACT_TX => L2Message::ContractTx(decode_unsigned_tx(body, false)?),
+        L2_MESSAGE_KIND_NON_MUTATING_CALL => L2Message::NonmutatingCall(Bytes::copy_from_slice(body)),
+        L2_MESSAGE_KIND_BATCH => {
+            if depth >= MAX_L2_MESSAGE_BATCH_DEPTH {
+                return Err(L2MessageError::BatchTooDeep);
+            }
+            // Nested frames: [8-byte BE length][sub-message]...
+            let mut inner = Vec::new();
+            let mut cursor = body;
+            while !cursor.is_empty() {
+                let offset = body.len() - cursor.len();
+                let frame_err = |reason: String| L2MessageError::BatchFrame { offset, reason };
+                if cursor.len() < 8 {
+                    return Err(frame_err(format!("truncated length prefix ({} bytes)", cursor.len())));
+                }
+                let len = u64::from_be_bytes(cursor[..8].try_into().unwrap());
+                if len > MAX_L2_MESSAGE_SIZE as u64 {
+                    return Err(frame_err(format!("sub-message length {len} exceeds {MAX_L2_MESSAGE_SIZE}")));
+                }
+                let len = len as usize;
+                if cursor.len() - 8 < len {
+                    return Err(frame_err(format!("sub-message length {len} exceeds the {} remaining bytes", cursor.len() - 8)));
+                }
+                inner.push(decode_l2_message_at_depth(&cursor[8..8 + len], depth + 1)?);
+                cursor = &cursor[8 + len..];
+            }
+            L2Message::Batch(inner)
+        }
+        L2_MESSAGE_KIND_SIGNED_TX => L2Message::SignedTx(Bytes::copy_from_slice(body)),
+        L2_MESSAGE_KIND_HEARTBEAT => L2Message::Heartbeat,
+        L2_MESSAGE_KIND_SIGNED_COMPRESSED_TX => L2Message::SignedCompressedTx(Bytes::copy_from_slice(body)),
+        _ => L2Message::Unknown { kind, payload: Bytes::copy_from_slice(body) },
+    };
+    Ok(message)
+}
+
+fn decode_unsigned_tx(body: &[u8], has_nonce: bool) -> Result<UnsignedTx, L2MessageError> {
+    let words = if has_nonce { 5 } else { 4 };
+    if body.len() < words * 32 {
+        return Err(L2MessageError::UnsignedTxTruncated { needed: words * 32, available: body.len() });
+    }
+    let mut fields = body[..words * 32].chunks_exact(32).map(U256::from_be_slice);
+    let mut next = || fields.next().expect("length checked above");
+    let gas_limit = next();
+    let max_fee_per_gas = next();
+    let nonce = has_nonce.then(&mut next);
+    let to = Address::from_word(next().to_be_bytes().into());
+    let value = next();
+    Ok(UnsignedTx {
+        gas_limit,
+        max_fee_per_gas,
+        nonce,
+        to: (to != Address::ZERO).then_some(to),
+        value,
+        data: Bytes::copy_from_slice(&body[words * 32..]),
+    })
+}
+
+/// Take the next RLP byte string from `cursor`, advancing it.
+fn take_rlp_string<'a>(cursor: &mut &'a [u8]) -> Result<&'a [u8], String> {
+    let mut buf = *cursor;
+    let header = Header::decode(&mut buf).map_err(|e| e.to_string())?;
+    if header.list {
+        return Err("expected an RLP byte string, found a list".into());
+    }
+    if buf.len() < header.payload_length {
+        return Err(format!(
+            "RLP string of {} bytes overruns the {} remaining bytes",
+            header.payload_length,
+            buf.len()
+        ));
+    }
+    let (item, rest) = buf.split_at(header.payload_length);
+    *cursor = rest;
+    Ok(item)
+}
+
+pub(crate) enum DecompressError {
+    TooLarge,
+    Invalid(String),
+}
+
+/// Brotli-decompress `data`, refusing outputs larger than `max_len`.
+pub(crate) fn brotli_decompress(data: &[u8], max_len: usize) -> Result<Vec<u8>, DecompressError> {
+    let mut decompressed = Vec::new();
+    let reader = brotli::Decompressor::new(data, 4096);
+    reader
+        .take(max_len as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| DecompressError::Invalid(e.to_string()))?;
+    if decompressed.len() > max_len {
+        return Err(DecompressError::TooLarge);
+    }
+    Ok(decompressed)
+}
@@ -0,0 +1,133 @@
+// Reassembly of multi-blob batches.
+// Nitro spreads one batch over every blob of the `SequencerBatchDelivered` transaction and
+// decodes them as a single stream, in the order of the transaction's `blob_versioned_hashes`.
+// A blob is only authentic for the batch once its KZG commitment hashes to the transaction's
+// versioned hash at the same position; what the blob source claims about it is not trusted.
+
+use alloy::{
+    eips::eip4844::{kzg_to_versioned_hash, Bytes48},
+    primitives::B256,
+};
+use thiserror::Error;
+
+use crate::nitro::{
+    blob::{decode_blobs, BlobCodecError},
+    kzg::{KzgError, KzgVerifier},
+};
+
+/// A blob as returned by a blob source, before it is matched against the batch transaction.
+#[derive(Debug, Clone)]
+pub struct FetchedBlob {
+    pub versioned_hash: B256,
+    /// Position of the blob inside its transaction, if the source reports one.
+    pub index: Option<usize>,
+    pub data: Vec<u8>,
+    /// Blob KZG proof, if the source supplies one.
+    pub proof: Option<Bytes48>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BlobAssemblyError {
+    #[error("batch transaction carries no blob versioned hashes")]
+    NoVersionedHashes,
+    #[error("blob {index} of the batch transaction ({versioned_hash}) is missing")]
+    MissingBlob { index: usize, versioned_hash: B256 },
+    #[error("blob {versioned_hash} is at position {expected} in the batch transaction but the source reported position {reported}")]
+    OutOfOrder { versioned_hash: B256, expected: usize, reported: usize },
+    #[error("blob {0} was supplied more than once")]
+    DuplicateBlob(B256),
+    #[error("batch transaction carries {expected} blob(s) but {found} commitment(s) were computed")]
+    CommitmentCount { expected: usize, found: usize },
+    #[error("blob {index}: commitment hashes to {computed} but the batch transaction committed to {expected}")]
+    VersionedHashMismatch { index: usize, expected: B256, computed: B256 },
+    #[error(transparent)]
+    Kzg(#[from] KzgError),
+    #[error(transparent)]
+    Codec(#[from] BlobCodecError),
+}
+
+/// Order `fetched` by the batch transaction's `versioned_hashes`.
+/// Blobs that don't belong to the transaction (e.g. other rollups' blobs in the same block) are dropped.
+pub fn order_batch_blobs(
+    versioned_hashes: &[B256],
+    fetched: Vec<FetchedBlob>,
+) -> Result<Vec<Vec<u8>>, BlobAssemblyError> {
+    if versioned_hashes.is_empty() {
+        return Err(BlobAssemblyError::NoVersionedHashes);
+    }
+
+    let mut slots: Vec<Option<Vec<u8>>> = vec![None; versioned_hashes.len()];
+    for blob in fetched {
+        let Some(expected) = versioned_hashes.iter().position(|h| *h == blob.versioned_hash) else {
+            continue;
+        };
+        if let Some(reported) = blob.index {
+            if reported != expected {
+                return Err(BlobAssemblyError::OutOfOrder {
+                    versioned_hash: blob.versioned_hash,
+                    expected,
+                    reported,
+                });
+            }
+        }
+        if slots[expected].replace(blob.data).is_some() {
+            return Err(BlobAssemblyError::DuplicateBlob(blob.versioned_hash));
+        }
+    }
+
+    slots
+        .into_iter()
+        .zip(versioned_hashes)
+        .enumerate()
+        .map(|(index, (slot, versioned_hash))| {
+            slot.ok_or(BlobAssemblyError::MissingBlob { index, versioned_hash: *versioned_hash })
+        })
+        .collect()
+}
+
+/// Check that the KZG commitment of blob `i` hashes to the batch transaction's `i`-th versioned hash.
+pub fn check_versioned_hashes(versioned_hashes: &[B256], commitments: &[Bytes48]) -> Result<(), BlobAssemblyError> {
+    if commitments.len() != versioned_hashes.len() {
+        return Err(BlobAssemblyError::CommitmentCount {
+            expected: versioned_hashes.len(),
+            found: commitments.len(),
+        });
+    }
+    for (index, (expected, commitment)) in versioned_hashes.iter().zip(commitments).enumerate() {
+        let computed = kzg_to_versioned_hash(commitment.as_slice());
+        if computed != *expected {
+            return Err(BlobAssemblyError::VersionedHashMismatch { index, expected: *expected, computed });
+        }
+    }
+    Ok(())
+}
+
+/// Order `fetched` by the batch transaction's `versioned_hashes` and authenticate it: every blob's
+/// commitment must hash to its versioned hash, and the KZG proofs are verified when the source
+/// supplied one for every blob.
+pub fn authenticate_batch_blobs(
+    kzg: &KzgVerifier,
+    versioned_hashes: &[B256],
+    fetched: Vec<FetchedBlob>,
+) -> Result<Vec<Vec<u8>>, BlobAssemblyError> {
+    let proofs: Option<Vec<_>> = versioned_hashes
+        .iter()
+        .map(|hash| fetched.iter().find(|blob| blob.versioned_hash == *hash).and_then(|blob| blob.proof))
+        .collect();
+    let ordered = order_batch_blobs(versioned_hashes, fetched)?;
+    let commitments = kzg.commitments(&ordered)?;
+    check_versioned_hashes(versioned_hashes, &commitments)?;
+    if let Some(proofs) = proofs {
+        kzg.verify_proofs(&ordered, &commitments, &proofs)?;
+    }
+    Ok(ordered)
+}
+
+/// Order, unpack and concatenate every blob of a batch transaction into the batch data.
+pub fn decode_batch_blobs(
+    versioned_hashes: &[B256],
+    fetched: Vec<FetchedBlob>,
+) -> Result<Vec<u8>, BlobAssemblyError> {
+    let ordered = order_batch_blobs(versioned_hashes, fetched)?;
+    Ok(decode_blobs(&ordered)?)
+}
@@ -0,0 +1,165 @@
+// Nitro's EIP-4844 blob codec.
+// Referencing to: https://github.com/OffchainLabs/nitro/blob/master/util/blobs/blobs.go
+//
+// The batch data is RLP-encoded as a single byte string (this is the length framing),
+// then spread over one or more blobs. Inside each blob:
+//   * bytes [1..32] of every field element carry 31 payload bytes, filled in order
+//     (field element 0 first, so the RLP header sits at blob[1..]),
+//   * the low 6 bits of byte [0] of every field element carry a further 6 bits,
+//     which are packed little-endian into 4096 * 6 / 8 = 3072 extra payload bytes
+//     that follow the 31-byte chunks of that blob.
+// The top 2 bits of every field element stay zero, so each one is below the BLS modulus.
+// Multi-blob batches simply continue the stream in the next blob.
+
+use alloy::rlp::{self, Header};
+use thiserror::Error;
+
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT;
+
+/// Number of bits in a BLS scalar that aren't part of a whole byte (floor(log2(BLS_MODULUS)) % 8).
+pub const SPARE_BLOB_BITS: usize = 6;
+
+/// Payload bytes carried by the 31 low-order bytes of every field element.
+pub const BYTES_PER_BLOB_CHUNKS: usize = FIELD_ELEMENTS_PER_BLOB * (BYTES_PER_FIELD_ELEMENT - 1);
+
+/// Payload bytes carried by the spare bits of the leading byte of every field element.
+pub const BYTES_PER_BLOB_SPARE_BITS: usize = FIELD_ELEMENTS_PER_BLOB * SPARE_BLOB_BITS / 8;
+
+/// Total payload capacity of a single blob (130,048 bytes).
+pub const USABLE_BYTES_PER_BLOB: usize = BYTES_PER_BLOB_CHUNKS + BYTES_PER_BLOB_SPARE_BITS;
+
+/// BLS12-381 scalar field modulus, big-endian.
+pub const BLS_MODULUS: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BlobCodecError {
+    #[error("blob {blob}: expected {BYTES_PER_BLOB} bytes, got {len}")]
+    InvalidBlobLength { blob: usize, len: usize },
+    #[error("blob {blob}: field element {index} has its top bits set (leading byte {byte:#04x})")]
+    NonCanonicalFieldElement { blob: usize, index: usize, byte: u8 },
+    #[error("no blobs to decode")]
+    NoBlobs,
+    #[error("blob payload is not a valid RLP byte string: {0}")]
+    InvalidRlp(String),
+}
+
+/// Returns true if the 32-byte big-endian field element is below the BLS modulus.
+pub fn is_canonical_field_element(fe: &[u8]) -> bool {
+    fe.len() == BYTES_PER_FIELD_ELEMENT && fe < &BLS_MODULUS[..]
+}
+
+/// Unpack the raw payload stream carried by one blob (`USABLE_BYTES_PER_BLOB` bytes).
+/// This is the lossless layout transform only; it knows nothing about RLP framing.
+pub fn unpack_blob(blob: &[u8]) -> Result<Vec<u8>, BlobCodecError> {
+    unpack_blob_at(0, blob)
+}
+
+fn unpack_blob_at(blob_index: usize, blob: &[u8]) -> Result<Vec<u8>, BlobCodecError> {
+    if blob.len() != BYTES_PER_BLOB {
+        return Err(BlobCodecError::InvalidBlobLength { blob: blob_index, len: blob.len() });
+    }
+
+    let mut out = Vec::with_capacity(USABLE_BYTES_PER_BLOB);
+    for fe in blob.chunks_exact(BYTES_PER_FIELD_ELEMENT) {
+        out.extend_from_slice(&fe[1..]);
+    }
+
+    let mut acc: u16 = 0;
+    let mut acc_bits = 0usize;
+    for (index, fe) in blob.chunks_exact(BYTES_PER_FIELD_ELEMENT).enumerate() {
+        let byte = fe[0];
+        if byte >> SPARE_BLOB_BITS != 0 {
+            return Err(BlobCodecError::NonCanonicalFieldElement { blob: blob_index, index, byte });
+        }
+        acc |= (byte as u16) << acc_bits;
+        acc_bits += SPARE_BLOB_BITS;
+        if acc_bits >= 8 {
+            out.push(acc as u8);
+            acc >>= 8;
+            acc_bits -= 8;
+        }
+    }
+    Ok(out)
+}
+
+/// Pack up to `USABLE_BYTES_PER_BLOB` bytes of `data` into a single blob.
+/// Returns the blob and whatever part of `data` did not fit.
+pub fn pack_blob(data: &[u8]) -> (Vec<u8>, &[u8]) {
+    let mut blob = vec![0u8; BYTES_PER_BLOB];
+
+    let (chunks, mut rest) = data.split_at(data.len().min(BYTES_PER_BLOB_CHUNKS));
+    for (fe, chunk) in blob.chunks_exact_mut(BYTES_PER_FIELD_ELEMENT).zip(chunks.chunks(BYTES_PER_FIELD_ELEMENT - 1)) {
+        fe[1..1 + chunk.len()].copy_from_slice(chunk);
+    }
+
+    let mut acc: u16 = 0;
+    let mut acc_bits = 0usize;
+    for fe in blob.chunks_exact_mut(BYTES_PER_FIELD_ELEMENT) {
+        if acc_bits < SPARE_BLOB_BITS {
+            let Some((&next, tail)) = rest.split_first() else {
+                if acc_bits == 0 {
+                    break;
+                }
+                // Flush the remaining bits of the last byte.
+                fe[0] = (acc & ((1 << SPARE_BLOB_BITS) - 1)) as u8;
+                acc_bits = 0;
+                break;
+            };
+            acc |= (next as u16) << acc_bits;
+            acc_bits += 8;
+            rest = tail;
+        }
+        fe[0] = (acc & ((1 << SPARE_BLOB_BITS) - 1)) as u8;
+        acc >>= SPARE_BLOB_BITS;
+        acc_bits -= SPARE_BLOB_BITS;
+    }
+    debug_assert_eq!(acc_bits, 0);
+
+    (blob, rest)
+}
+
+/// Encode batch data into as many blobs as needed, following Nitro's `EncodeBlobs`.
+pub fn encode_blobs(data: &[u8]) -> Vec<Vec<u8>> {
+    let encoded = rlp::encode(data);
+    let mut remaining = &encoded[..];
+    let mut blobs = Vec::new();
+    while !remaining.is_empty() {
+        let (blob, rest) = pack_blob(remaining);
+        blobs.push(blob);
+        remaining = rest;
+    }
+    blobs
+}
+
+/// Decode the batch data carried by an ordered list of blobs, following Nitro's `DecodeBlobs`.
+/// Payloads of all blobs are concatenated before the RLP length framing is read, so a batch
+/// may span several blobs. Trailing padding after the RLP item is ignored.
+pub fn decode_blobs<B: AsRef<[u8]>>(blobs: &[B]) -> Result<Vec<u8>, BlobCodecError> {
+    if blobs.is_empty() {
+        return Err(BlobCodecError::NoBlobs);
+    }
+    let mut stream = Vec::with_capacity(blobs.len() * USABLE_BYTES_PER_BLOB);
+    for (i, blob) in blobs.iter().enumerate() {
+        stream.extend(unpack_blob_at(i, blob.as_ref())?);
+    }
+
+    let mut buf = &stream[..];
+    let header = Header::decode(&mut buf).map_err(|e| BlobCodecError::InvalidRlp(e.to_string()))?;
+    if header.list {
+        return Err(BlobCodecError::InvalidRlp("expected a byte string, found a list".into()));
+    }
+    if header.payload_length > buf.len() {
+        return Err(BlobCodecError::InvalidRlp(format!(
+            "declared length {} exceeds the {} bytes carried by {} blob(s)",
+            header.payload_length,
+            buf.len(),
+            blobs.len()
+        )));
+    }
+    Ok(buf[..header.payload_length].to_vec())
+}
@@ -0,0 +1,398 @@
+// Where the blobs of a batch transaction are fetched from.
+// Referencing to: https://ethereum.github.io/beacon-APIs/#/Beacon/getBlobSidecars
+// and https://api.blobscan.com (`GET /blobs/{versionedHash}`)
+//
+// Blobs are addressed by the beacon slot of the block that carried the batch transaction and by
+// their versioned hash. Sources are not trusted: whatever one returns is checked against the
+// transaction's versioned hashes (and the KZG proofs, when supplied), and a source serving blobs
+// that fail the check is passed over for the next one.
+//
+// Beacon nodes prune sidecars after 4096 epochs (~18 days), so the indexer archives every blob it
+// has verified and falls back through an ordered list of sources. Blobs that no source has are
+// permanently unavailable: their batch is recorded as such and skipped.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use alloy::{
+    eips::eip4844::{kzg_to_versioned_hash, Bytes48},
+    primitives::B256,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::nitro::{
+    batch_blobs::{authenticate_batch_blobs, FetchedBlob},
+    blob::BYTES_PER_BLOB,
+    kzg::KzgVerifier,
+};
+
+/// Default directory verified blobs are archived in.
+pub const DEFAULT_BLOB_DIRECTORY: &str = "blobs";
+/// Default log of batches skipped for unavailable blobs.
+pub const DEFAULT_UNAVAILABLE_BATCHES_PATH: &str = "unavailable_batches.jsonl";
+
+#[derive(Debug, Error)]
+pub enum BlobSourceError {
+    #[error("blob {versioned_hash} of slot {slot} is not available")]
+    NotFound { slot: u64, versioned_hash: B256 },
+    /// Every source answered, and none has the blobs.
+    #[error("blobs of slot {slot} are not available from any of {sources} source(s)")]
+    Unavailable { slot: u64, sources: usize },
+    /// No source has authentic blobs, and some served blobs that fail verification.
+    #[error("blobs of slot {slot} served by {sources} source(s) fail verification")]
+    Inauthentic { slot: u64, sources: usize },
+    #[error("invalid response from the blob source: {0}")]
+    InvalidResponse(String),
+    #[error("corrupt unavailable batch log: {0}")]
+    Corrupt(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+}
+
+/// Source of EIP-4844 blobs.
+#[async_trait]
+pub trait BlobSource: Send + Sync {
+    /// Short name used in logs.
+    fn name(&self) -> &str;
+
+    /// Return the blob with `versioned_hash` carried in the block of `slot`, unverified.
+    async fn fetch_blob(&self, slot: u64, versioned_hash: B256) -> Result<FetchedBlob, BlobSourceError>;
+
+    /// Return every blob of a batch transaction, in the order of `versioned_hashes`.
+    async fn fetch_blobs(&self, slot: u64, versioned_hashes: &[B256]) -> Result<Vec<FetchedBlob>, BlobSourceError> {
+        let mut fetched = Vec::with_capacity(versioned_hashes.len());
+        for versioned_hash in versioned_hashes {
+            fetched.push(self.fetch_blob(slot, *versioned_hash).await?);
+        }
+        Ok(fetched)
+    }
+}
+
+/// Blob bytes served either raw or as (`0x`-prefixed) hex text.
+fn decode_blob_bytes(body: &[u8]) -> Result<Vec<u8>, BlobSourceError> {
+    if body.len() == BYTES_PER_BLOB {
+        return Ok(body.to_vec());
+    }
+    let text = std::str::from_utf8(body).map_err(|_| {
+        BlobSourceError::InvalidResponse(format!("blob is {} bytes, expected {BYTES_PER_BLOB}", body.len()))
+    })?;
+    alloy::hex::decode(text.trim()).map_err(|e| BlobSourceError::InvalidResponse(format!("blob hex: {e}")))
+}
+
+fn hex_field<'a>(value: &'a serde_json::Value, field: &str) -> Result<&'a str, BlobSourceError> {
+    value
+        .get(field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| BlobSourceError::InvalidResponse(format!("missing `{field}`")))
+}
+
+fn bytes48_field(value: &serde_json::Value, field: &str) -> Result<Bytes48, BlobSourceError> {
+    hex_field(value, field)?.parse().map_err(|e| BlobSourceError::InvalidResponse(format!("`{field}`: {e}")))
+}
+
+/// A consensus-layer node (`GET {url}/eth/v1/beacon/blob_sidecars/{slot}`).
+/// Nodes only keep sidecars for about 18 days.
+#[derive(Debug, Clone)]
+pub struct BeaconBlobSource {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl BeaconBlobSource {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into().trim_end_matches('/').to_string(), client: reqwest::Client::new() }
+    }
+
+    /// The sidecars of `slot` with the versioned hash of their claimed commitment.
+    async fn sidecars(&self, slot: u64) -> Result<Vec<(B256, FetchedBlob)>, BlobSourceError> {
+        let url = format!("{}/eth/v1/beacon/blob_sidecars/{slot}", self.url);
+        let response = self.client.get(&url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        let body: serde_json::Value = response.error_for_status()?.json().await?;
+        let sidecars = body
+            .get("data")
+            .and_then(|d| d.as_array())
+            .ok_or_else(|| BlobSourceError::InvalidResponse("missing `data` array".to_string()))?;
+        sidecars
+            .iter()
+            .map(|sidecar| {
+                let versioned_hash = kzg_to_versioned_hash(bytes48_field(sidecar, "kzg_commitment")?.as_slice());
+                let blob = FetchedBlob {
+                    versioned_hash,
+                    // The sidecar index is the blob's position in the block, not in its transaction.
+                    index: None,
+                    data: decode_blob_bytes(hex_field(sidecar, "blob")?.as_bytes())?,
+                    proof: Some(bytes48_field(sidecar, "kzg_proof")?),
+                };
+                Ok((versioned_hash, blob))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl BlobSource for BeaconBlobSource {
+    fn name(&self) -> &str {
+        "beacon"
+    }
+
+    async fn fetch_blob(&self, slot: u64, versioned_hash: B256) -> Result<FetchedBlob, BlobSourceError> {
+        Ok(self.fetch_blobs(slot, &[versioned_hash]).await?.remove(0))
+    }
+
+    /// One request for the whole block, keeping the sidecars of `versioned_hashes`.
+    async fn fetch_blobs(&self, slot: u64, versioned_hashes: &[B256]) -> Result<Vec<FetchedBlob>, BlobSourceError> {
+        let mut sidecars = self.sidecars(slot).await?;
+        versioned_hashes
+            .iter()
+            .map(|versioned_hash| {
+                sidecars
+                    .iter()
+                    .position(|(hash, _)| hash == versioned_hash)
+                    .map(|i| sidecars.swap_remove(i).1)
+                    .ok_or(BlobSourceError::NotFound { slot, versioned_hash: *versioned_hash })
+            })
+            .collect()
+    }
+}
+
+/// The Blobscan API (`GET {url}/blobs/{versioned_hash}`), which keeps blobs past the beacon
+/// retention window. The blob is read inline when the response carries it, otherwise from its
+/// `dataStorageReferences` in order.
+#[derive(Debug, Clone)]
+pub struct BlobscanBlobSource {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl BlobscanBlobSource {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into().trim_end_matches('/').to_string(), client: reqwest::Client::new() }
+    }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>, BlobSourceError> {
+        let body = self.client.get(url).send().await?.error_for_status()?.bytes().await?;
+        decode_blob_bytes(&body)
+    }
+}
+
+#[async_trait]
+impl BlobSource for BlobscanBlobSource {
+    fn name(&self) -> &str {
+        "blobscan"
+    }
+
+    async fn fetch_blob(&self, slot: u64, versioned_hash: B256) -> Result<FetchedBlob, BlobSourceError> {
+        let url = format!("{}/blobs/{versioned_hash}", self.url);
+        let response = self.client.get(&url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(BlobSourceError::NotFound { slot, versioned_hash });
+        }
+        let body: serde_json::Value = response.error_for_status()?.json().await?;
+        let proof = body.get("proof").and_then(|v| v.as_str()).and_then(|v| v.parse().ok());
+
+        let data = match body.get("data").and_then(|v| v.as_str()) {
+            Some(data) => decode_blob_bytes(data.as_bytes())?,
+            None => {
+                let urls: Vec<&str> = body
+                    .get("dataStorageReferences")
+                    .and_then(|refs| refs.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|reference| reference.get("url").and_then(|url| url.as_str()))
+                    .collect();
+                let mut last_error = BlobSourceError::NotFound { slot, versioned_hash };
+                let mut data = None;
+                for url in urls {
+                    match self.download(url).await {
+                        Ok(blob) => {
+                            data = Some(blob);
+                            break;
+                        }
+                        Err(e) => last_error = e,
+                    }
+                }
+                data.ok_or(last_error)?
+            }
+        };
+        Ok(FetchedBlob { versioned_hash, index: None, data, proof })
+    }
+}
+
+/// Raw blobs stored as files named by the lowercase hex versioned hash (no `0x`) in one
+/// directory, e.g. captured with [`DirectoryBlobSource::store`] for offline replay.
+#[derive(Debug, Clone)]
+pub struct DirectoryBlobSource {
+    root: PathBuf,
+}
+
+impl DirectoryBlobSource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn path_for(&self, versioned_hash: B256) -> PathBuf {
+        self.root.join(alloy::hex::encode(versioned_hash))
+    }
+
+    /// Write `blob` into the directory, creating it if needed. Blobs already stored are kept; new
+    /// ones are written to a temporary file and renamed, so a crash never leaves a partial blob.
+    pub async fn store(&self, blob: &FetchedBlob) -> Result<(), BlobSourceError> {
+        let path = self.path_for(blob.versioned_hash);
+        if tokio::fs::try_exists(&path).await? {
+            return Ok(());
+        }
+        tokio::fs::create_dir_all(&self.root).await?;
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, &blob.data).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BlobSource for DirectoryBlobSource {
+    fn name(&self) -> &str {
+        "directory"
+    }
+
+    async fn fetch_blob(&self, slot: u64, versioned_hash: B256) -> Result<FetchedBlob, BlobSourceError> {
+        match tokio::fs::read(self.path_for(versioned_hash)).await {
+            Ok(data) => Ok(FetchedBlob { versioned_hash, index: None, data: decode_blob_bytes(&data)?, proof: None }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(BlobSourceError::NotFound { slot, versioned_hash })
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Sources tried in order until one returns every blob of the batch transaction, authentic.
+pub struct FallbackBlobSource {
+    sources: Vec<Box<dyn BlobSource>>,
+    name: String,
+    kzg: KzgVerifier,
+}
+
+impl FallbackBlobSource {
+    pub fn new(sources: Vec<Box<dyn BlobSource>>, kzg: KzgVerifier) -> Self {
+        let name = sources.iter().map(|source| source.name()).collect::<Vec<_>>().join(" -> ");
+        Self { sources, name, kzg }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+}
+
+#[async_trait]
+impl BlobSource for FallbackBlobSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch_blob(&self, slot: u64, versioned_hash: B256) -> Result<FetchedBlob, BlobSourceError> {
+        Ok(self.fetch_blobs(slot, &[versioned_hash]).await?.remove(0))
+    }
+
+    /// The verified blobs, in the order of `versioned_hashes`, from the first source that has all
+    /// of them. Fails with [`BlobSourceError::Unavailable`] only if every source reported them
+    /// missing; otherwise the last transient error, or [`BlobSourceError::Inauthentic`], is
+    /// returned so the batch is retried.
+    async fn fetch_blobs(&self, slot: u64, versioned_hashes: &[B256]) -> Result<Vec<FetchedBlob>, BlobSourceError> {
+        let mut transient = None;
+        let mut inauthentic = 0;
+        for source in &self.sources {
+            let blobs = match source.fetch_blobs(slot, versioned_hashes).await {
+                Ok(blobs) => blobs,
+                Err(BlobSourceError::NotFound { .. } | BlobSourceError::Unavailable { .. }) => continue,
+                Err(e) => {
+                    println!("Blob source {} failed for slot {slot}: {e}", source.name());
+                    transient = Some(e);
+                    continue;
+                }
+            };
+            match authenticate_batch_blobs(&self.kzg, versioned_hashes, blobs) {
+                Ok(ordered) => {
+                    return Ok(versioned_hashes
+                        .iter()
+                        .zip(ordered)
+                        .enumerate()
+                        .map(|(index, (versioned_hash, data))| FetchedBlob {
+                            versioned_hash: *versioned_hash,
+                            index: Some(index),
+                            data,
+                            proof: None,
+                        })
+                        .collect())
+                }
+                Err(e) => {
+                    println!("Blob source {} served blobs of slot {slot} that fail verification: {e}", source.name());
+                    inauthentic += 1;
+                }
+            }
+        }
+        Err(transient.unwrap_or(if inauthentic > 0 {
+            BlobSourceError::Inauthentic { slot, sources: inauthentic }
+        } else {
+            BlobSourceError::Unavailable { slot, sources: self.sources.len() }
+        }))
+    }
+}
+
+/// A batch skipped because its blobs are permanently unavailable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnavailableBatch {
+    pub batch_sequence_number: u64,
+    pub l1_block: u64,
+    pub slot: u64,
+    pub versioned_hashes: Vec<B256>,
+}
+
+/// Batches with unavailable blobs, appended as one JSON line each.
+#[derive(Debug, Clone)]
+pub struct UnavailableBatchLog {
+    path: PathBuf,
+}
+
+impl UnavailableBatchLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, batch: &UnavailableBatch) -> Result<(), BlobSourceError> {
+        let mut line = serde_json::to_vec(batch)?;
+        line.push(b'\n');
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(&line)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Every recorded batch, oldest first; empty if nothing was recorded yet.
+    pub fn load(&self) -> Result<Vec<UnavailableBatch>, BlobSourceError> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        data.lines().filter(|line| !line.trim().is_empty()).map(|line| Ok(serde_json::from_str(line)?)).collect()
+    }
+}
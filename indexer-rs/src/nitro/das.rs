@@ -0,0 +1,264 @@
+// AnyTrust data availability certificates and the readers that resolve them.
+// Referencing to: https://github.com/OffchainLabs/nitro/blob/master/arbstate/daprovider/util.go
+// (DeserializeDASCertFrom, RecoverPayloadFromDasBatch) and https://github.com/OffchainLabs/nitro/blob/master/das/dastree/dastree.go
+//
+// On AnyTrust chains (Arbitrum Nova, AnyTrust Orbit chains) the payload after the 40-byte header
+// is a certificate signed by the Data Availability Committee; the batch data itself is served
+// by the committee's REST endpoints, keyed by its hash.
+//
+// Certificate layout: flag (1) | keyset hash (32) | data hash (32) | timeout (8, BE)
+//                     | version (1, only with the tree-DAS flag) | signers mask (8, BE) | BLS signature (96)
+//
+// The BLS signature is decoded but not verified against the committee keyset.
+
+use std::path::PathBuf;
+
+use alloy::primitives::{keccak256, FixedBytes, B256};
+use async_trait::async_trait;
+use base64::Engine;
+use thiserror::Error;
+
+use crate::nitro::batch::{BatchDecodeError, BatchHeader, HeaderFlags, SequencerBatch};
+use crate::utils::constants::*;
+
+/// Size of the bins the data is split into before building the DAS tree.
+pub const DAS_TREE_BIN_SIZE: usize = 64 * 1024;
+const DAS_TREE_LEAF_BYTE: u8 = 0xfe;
+const DAS_TREE_NODE_BYTE: u8 = 0xff;
+/// Minimum time a certificate must stay valid after the batch's max timestamp.
+pub const MIN_DAS_CERT_LIFETIME_SECS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Error)]
+pub enum DasError {
+    #[error("header byte {0:#04x} does not carry the DAS flag")]
+    NotDas(u8),
+    #[error("DAS certificate truncated in {field}: need {needed} bytes, {available} available")]
+    Truncated { field: &'static str, needed: usize, available: usize },
+    /// Like expired certificates, logged by Nitro with the batch treated as empty.
+    #[error("unsupported DAS certificate version {0}")]
+    UnsupportedVersion(u8),
+    /// Nitro logs these and treats the batch as empty: only its delayed messages apply.
+    #[error("certificate timeout {timeout} is less than a week after the batch's max timestamp {max_timestamp}")]
+    ExpiredCertificate { timeout: u64, max_timestamp: u64 },
+    #[error("no data available for hash {0}")]
+    NotFound(B256),
+    #[error("data returned for {0} does not match its hash")]
+    HashMismatch(B256),
+    #[error("invalid response from the DAS endpoint: {0}")]
+    InvalidResponse(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("batch data: {0}")]
+    Batch(#[from] BatchDecodeError),
+}
+
+/// A Data Availability Committee certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DasCertificate {
+    /// Flag byte the certificate was posted with.
+    pub header_byte: u8,
+    pub keyset_hash: B256,
+    /// Version 0: `keccak256(data)`; version 1: DAS tree root of the data.
+    pub data_hash: B256,
+    /// Unix time until which the committee promised to serve the data.
+    pub timeout: u64,
+    /// Bit `i` set when committee member `i` signed.
+    pub signers_mask: u64,
+    pub signature: FixedBytes<96>,
+    pub version: u8,
+}
+
+impl DasCertificate {
+    /// Decode a certificate from the payload that follows the 40-byte batch header.
+    pub fn decode(payload: &[u8]) -> Result<Self, DasError> {
+        let Some((&header_byte, mut rest)) = payload.split_first() else {
+            return Err(DasError::Truncated { field: "header byte", needed: 1, available: 0 });
+        };
+        if header_byte & DASMESSAGE_HEADER_FLAG == 0 {
+            return Err(DasError::NotDas(header_byte));
+        }
+        let mut take = |field: &'static str, needed: usize| -> Result<&[u8], DasError> {
+            if rest.len() < needed {
+                return Err(DasError::Truncated { field, needed, available: rest.len() });
+            }
+            let (head, tail) = rest.split_at(needed);
+            rest = tail;
+            Ok(head)
+        };
+        let u64_be = |b: &[u8]| u64::from_be_bytes(b.try_into().expect("8 bytes"));
+
+        let keyset_hash = B256::from_slice(take("keyset hash", 32)?);
+        let data_hash = B256::from_slice(take("data hash", 32)?);
+        let timeout = u64_be(take("timeout", 8)?);
+        let version = if header_byte & TREE_DAS_MESSAGE_HEADER_FLAG != 0 { take("version", 1)?[0] } else { 0 };
+        let signers_mask = u64_be(take("signers mask", 8)?);
+        let signature = FixedBytes::<96>::from_slice(take("signature", 96)?);
+
+        Ok(Self { header_byte, keyset_hash, data_hash, timeout, signers_mask, signature, version })
+    }
+
+    /// Number of committee members that signed.
+    pub fn signer_count(&self) -> u32 {
+        self.signers_mask.count_ones()
+    }
+}
+
+/// Root of the DAS tree over `preimage`: 64 KiB bins hashed into leaves
+/// `keccak256(0xfe || keccak256(bin))`, paired into nodes
+/// `keccak256(0xff || left || right || size_under as u32 BE)`, odd nodes bubbling up.
+pub fn das_tree_hash(preimage: &[u8]) -> B256 {
+    let leaf = |bin: &[u8]| {
+        let mut data = vec![DAS_TREE_LEAF_BYTE];
+        data.extend_from_slice(keccak256(bin).as_slice());
+        keccak256(data)
+    };
+    let node = |left: &B256, right: &B256, size: u32| {
+        let mut data = vec![DAS_TREE_NODE_BYTE];
+        data.extend_from_slice(left.as_slice());
+        data.extend_from_slice(right.as_slice());
+        data.extend_from_slice(&size.to_be_bytes());
+        keccak256(data)
+    };
+
+    // Nitro hashes an empty preimage as a single empty bin.
+    if preimage.is_empty() {
+        return leaf(&[]);
+    }
+
+    let mut layer: Vec<(B256, u32)> =
+        preimage.chunks(DAS_TREE_BIN_SIZE).map(|bin| (leaf(bin), bin.len() as u32)).collect();
+    while layer.len() > 1 {
+        layer = layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [(l, ls), (r, rs)] => (node(l, r, ls + rs), ls + rs),
+                [odd] => *odd,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    layer[0].0
+}
+
+/// Whether `preimage` is the data behind `hash` for a certificate of `version`.
+pub fn is_valid_preimage(version: u8, hash: B256, preimage: &[u8]) -> bool {
+    match version {
+        0 => keccak256(preimage) == hash,
+        _ => das_tree_hash(preimage) == hash,
+    }
+}
+
+/// Version 0 certificates carry flat keccak hashes; the committee stores data under the tree hash.
+pub fn flat_hash_to_tree_hash(flat: B256) -> B256 {
+    let mut data = vec![DAS_TREE_LEAF_BYTE];
+    data.extend_from_slice(flat.as_slice());
+    keccak256(data)
+}
+
+/// Source of data committed to by DAS certificates.
+#[async_trait]
+pub trait DataAvailabilityReader: Send + Sync {
+    /// Return the preimage stored under `hash`, unverified.
+    async fn get_by_hash(&self, hash: B256) -> Result<Vec<u8>, DasError>;
+}
+
+/// A committee member's REST endpoint (`GET {url}/get-by-hash/{hash}` -> `{"data": "<base64>"}`).
+#[derive(Debug, Clone)]
+pub struct RestDasReader {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl RestDasReader {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into().trim_end_matches('/').to_string(), client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait]
+impl DataAvailabilityReader for RestDasReader {
+    async fn get_by_hash(&self, hash: B256) -> Result<Vec<u8>, DasError> {
+        let url = format!("{}/get-by-hash/{}", self.url, alloy::hex::encode(hash));
+        let response = self.client.get(&url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(DasError::NotFound(hash));
+        }
+        let body: serde_json::Value = response.error_for_status()?.json().await?;
+        let data = body
+            .get("data")
+            .and_then(|d| d.as_str())
+            .ok_or_else(|| DasError::InvalidResponse("missing `data` field".to_string()))?;
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| DasError::InvalidResponse(e.to_string()))
+    }
+}
+
+/// Preimages stored as files named by the lowercase hex hash (no `0x`) in one directory.
+#[derive(Debug, Clone)]
+pub struct DirectoryDasReader {
+    root: PathBuf,
+}
+
+impl DirectoryDasReader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn path_for(&self, hash: B256) -> PathBuf {
+        self.root.join(alloy::hex::encode(hash))
+    }
+}
+
+#[async_trait]
+impl DataAvailabilityReader for DirectoryDasReader {
+    async fn get_by_hash(&self, hash: B256) -> Result<Vec<u8>, DasError> {
+        match tokio::fs::read(self.path_for(hash)).await {
+            Ok(data) => Ok(data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(DasError::NotFound(hash)),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Fetch the data behind `hash` and check it against the certificate version's hash function.
+pub async fn fetch_verified<R: DataAvailabilityReader + ?Sized>(
+    reader: &R,
+    version: u8,
+    hash: B256,
+) -> Result<Vec<u8>, DasError> {
+    let preimage = if version == 0 {
+        match reader.get_by_hash(flat_hash_to_tree_hash(hash)).await {
+            Ok(preimage) => preimage,
+            Err(_) => reader.get_by_hash(hash).await?,
+        }
+    } else {
+        reader.get_by_hash(hash).await?
+    };
+    if !is_valid_preimage(version, hash, &preimage) {
+        return Err(DasError::HashMismatch(hash));
+    }
+    Ok(preimage)
+}
+
+/// Resolve a DAS batch: decode the certificate, fetch and verify the data it commits to and
+/// decode that data as a regular sequencer payload.
+pub async fn decode_das_batch<R: DataAvailabilityReader + ?Sized>(
+    reader: &R,
+    header: BatchHeader,
+    payload: &[u8],
+) -> Result<(DasCertificate, SequencerBatch), DasError> {
+    let cert = DasCertificate::decode(payload)?;
+    if cert.version >= 2 {
+        return Err(DasError::UnsupportedVersion(cert.version));
+    }
+    if cert.timeout < header.max_timestamp.saturating_add(MIN_DAS_CERT_LIFETIME_SECS) {
+        return Err(DasError::ExpiredCertificate { timeout: cert.timeout, max_timestamp: header.max_timestamp });
+    }
+    let data = fetch_verified(reader, cert.version, cert.data_hash).await?;
+    let mut batch = SequencerBatch::decode(header, &data)?;
+    batch.flags = Some(HeaderFlags(cert.header_byte));
+    Ok((cert, batch))
+}
@@ -0,0 +1,182 @@
+// Delayed (L1 -> L2) messages consumed by sequencer batches.
+// Referencing to: https://github.com/OffchainLabs/nitro/blob/master/arbstate/inbox.go (delayed segments)
+// and https://github.com/OffchainLabs/nitro/blob/master/arbos/parse_l2.go (ParseL2Transactions)
+//
+// Every `BATCH_SEGMENT_KIND_DELAYED_MESSAGES` segment consumes the next message of the delayed
+// inbox. Messages between the last consumed one and the batch's `afterDelayedMessagesRead` are
+// consumed after the last segment ("virtual" delayed segments).
+
+use alloy::{
+    primitives::{keccak256, Address, Bytes, B256, U256},
+    rlp::{Encodable, Header},
+};
+use thiserror::Error;
+
+use crate::nitro::batch::{decode_l2_message, L2Message, L2MessageError, Segment, SequencerBatch};
+use crate::nitro::retryable::{decode_submit_retryable, RetryableError, SubmitRetryableTx};
+use crate::utils::constants::*;
+
+/// Transaction type byte of `ArbitrumDepositTx`.
+pub const ARBITRUM_DEPOSIT_TX_TYPE: u8 = 0x64;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DelayedMessageError {
+    #[error("afterDelayedMessagesRead {after} is below the {before} delayed messages already read")]
+    CountBackwards { before: u64, after: u64 },
+    #[error("segment {segment} reads past the batch's afterDelayedMessagesRead ({after})")]
+    ReadPastBatchCount { segment: usize, after: u64 },
+    #[error("delayed message {index} (kind {kind}): payload too short ({len} bytes)")]
+    Truncated { index: u64, kind: u8, len: usize },
+    #[error("delayed message {index}: {source}")]
+    L2Message { index: u64, source: L2MessageError },
+    #[error("delayed message {index}: {source}")]
+    Retryable { index: u64, source: RetryableError },
+}
+
+/// Where a batch consumes a delayed message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DelayedPointer {
+    /// Index of the delayed segment in `SequencerBatch::segments`; `None` for messages
+    /// consumed after the last segment.
+    pub segment: Option<usize>,
+    /// Index of the message in the delayed inbox.
+    pub message_index: u64,
+}
+
+/// A message of the delayed inbox, as delivered by the Bridge (`MessageDelivered`) and the
+/// inbox contract that carried its data (`InboxMessageDelivered[FromOrigin]`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelayedMessage {
+    pub index: u64,
+    pub kind: u8,
+    /// Sender as recorded by the Bridge (already aliased for contract senders).
+    pub sender: Address,
+    pub l1_block: u64,
+    pub timestamp: u64,
+    pub base_fee_l1: U256,
+    pub data: Bytes,
+}
+
+impl DelayedMessage {
+    /// Nitro's request ID for this message: the delayed message index as a 32-byte word.
+    pub fn request_id(&self) -> B256 {
+        B256::from(U256::from(self.index))
+    }
+}
+
+/// `ArbitrumDepositTx`, the L2 transaction minted for an `L1_MESSAGE_TYPE_ETH_DEPOSIT` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositTx {
+    pub chain_id: u64,
+    pub l1_request_id: B256,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+}
+
+impl DepositTx {
+    /// L2 transaction hash: `keccak256(0x64 || rlp([chainId, l1RequestId, from, to, value]))`.
+    pub fn hash(&self) -> B256 {
+        let payload_length = self.chain_id.length()
+            + self.l1_request_id.length()
+            + self.from.length()
+            + self.to.length()
+            + self.value.length();
+        let mut out = vec![ARBITRUM_DEPOSIT_TX_TYPE];
+        Header { list: true, payload_length }.encode(&mut out);
+        self.chain_id.encode(&mut out);
+        self.l1_request_id.encode(&mut out);
+        self.from.encode(&mut out);
+        self.to.encode(&mut out);
+        self.value.encode(&mut out);
+        keccak256(out)
+    }
+}
+
+/// The synthetic L2 transaction(s) a delayed message turns into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DelayedTx {
+    EthDeposit { hash: B256, tx: DepositTx },
+    /// `ArbitrumSubmitRetryableTx`; its hash is also the retryable ticket ID.
+    SubmitRetryable { ticket_id: B256, tx: SubmitRetryableTx },
+    /// An L2 message sent through the delayed inbox (signed, unsigned or contract txs).
+    L2Message { request_id: B256, sender: Address, message: L2Message },
+    /// An unsigned or contract tx whose gas is paid on L1.
+    L2FundedByL1 { request_id: B256, sender: Address, message: L2Message },
+    /// Messages that produce no user transaction (end of block, rollup events, batch posting reports...).
+    Ignored { kind: u8 },
+}
+
+/// Map every delayed message consumed by `batch` to the segment that consumes it.
+/// `delayed_read_before` is the `afterDelayedMessagesRead` of the previous batch.
+pub fn delayed_message_pointers(
+    batch: &SequencerBatch,
+    delayed_read_before: u64,
+) -> Result<Vec<DelayedPointer>, DelayedMessageError> {
+    let after = batch.header.after_delayed_messages;
+    if after < delayed_read_before {
+        return Err(DelayedMessageError::CountBackwards { before: delayed_read_before, after });
+    }
+
+    let mut pointers = Vec::with_capacity((after - delayed_read_before) as usize);
+    let mut next = delayed_read_before;
+    for (segment, _) in batch
+        .segments
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| matches!(s, Segment::DelayedMessages))
+    {
+        if next >= after {
+            return Err(DelayedMessageError::ReadPastBatchCount { segment, after });
+        }
+        pointers.push(DelayedPointer { segment: Some(segment), message_index: next });
+        next += 1;
+    }
+    pointers.extend((next..after).map(|message_index| DelayedPointer { segment: None, message_index }));
+    Ok(pointers)
+}
+
+/// Materialize a delayed message as the synthetic L2 transaction Nitro derives from it.
+pub fn materialize_delayed_message(msg: &DelayedMessage, chain_id: u64) -> Result<DelayedTx, DelayedMessageError> {
+    let request_id = msg.request_id();
+    let l2_message = |data: &[u8]| {
+        decode_l2_message(data).map_err(|source| DelayedMessageError::L2Message { index: msg.index, source })
+    };
+
+    let tx = match msg.kind {
+        L1_MESSAGE_TYPE_ETH_DEPOSIT => {
+            // abi.encodePacked(address to, uint256 value)
+            if msg.data.len() < 20 + 32 {
+                return Err(DelayedMessageError::Truncated { index: msg.index, kind: msg.kind, len: msg.data.len() });
+            }
+            let tx = DepositTx {
+                chain_id,
+                l1_request_id: request_id,
+                from: msg.sender,
+                to: Address::from_slice(&msg.data[..20]),
+                value: U256::from_be_slice(&msg.data[20..52]),
+            };
+            DelayedTx::EthDeposit { hash: tx.hash(), tx }
+        }
+        L1_MESSAGE_TYPE_SUBMIT_RETRYABLE_TX => {
+            let retryable = decode_submit_retryable(&msg.data)
+                .map_err(|source| DelayedMessageError::Retryable { index: msg.index, source })?;
+            let tx = SubmitRetryableTx {
+                chain_id,
+                request_id,
+                from: msg.sender,
+                l1_base_fee: msg.base_fee_l1,
+                retryable,
+            };
+            DelayedTx::SubmitRetryable { ticket_id: tx.ticket_id(), tx }
+        }
+        L1_MESSAGE_TYPE_L2_MESSAGE => {
+            DelayedTx::L2Message { request_id, sender: msg.sender, message: l2_message(&msg.data)? }
+        }
+        L1_MESSAGE_TYPE_L2_FUNDED_BY_L1 => {
+            DelayedTx::L2FundedByL1 { request_id, sender: msg.sender, message: l2_message(&msg.data)? }
+        }
+        kind => DelayedTx::Ignored { kind },
+    };
+    Ok(tx)
+}
@@ -0,0 +1,217 @@
+// Forensic analysis of sequencer payloads the strict decoder rejects.
+//
+// Nothing here is used to index data: the scanner guesses segment boundaries by trying several
+// length-prefix encodings (kind (1 byte) || length (4 or 8 bytes, BE or LE) || payload) and by
+// skipping up to `MAX_RESYNC_SKIP` bytes when no plausible header is found. Every guess is
+// reported as a candidate parse with a confidence score, for a human to investigate.
+
+use std::fmt;
+
+use crate::nitro::batch::{
+    brotli_decompress, decode_segment, BatchDecodeError, BatchHeader, Segment, SequencerBatch, MAX_DECOMPRESSED_LEN,
+};
+
+/// Bytes the scanner may skip to find the next plausible segment header.
+pub const MAX_RESYNC_SKIP: usize = 32;
+
+/// Length prefix shapes the scanner tries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthEncoding {
+    U64Be,
+    U64Le,
+    U32Be,
+    U32Le,
+}
+
+impl LengthEncoding {
+    pub const ALL: [LengthEncoding; 4] =
+        [LengthEncoding::U64Be, LengthEncoding::U64Le, LengthEncoding::U32Be, LengthEncoding::U32Le];
+
+    fn width(self) -> usize {
+        match self {
+            LengthEncoding::U64Be | LengthEncoding::U64Le => 8,
+            LengthEncoding::U32Be | LengthEncoding::U32Le => 4,
+        }
+    }
+
+    fn read(self, bytes: &[u8]) -> usize {
+        match self {
+            LengthEncoding::U64Be => u64::from_be_bytes(bytes.try_into().unwrap()) as usize,
+            LengthEncoding::U64Le => u64::from_le_bytes(bytes.try_into().unwrap()) as usize,
+            LengthEncoding::U32Be => u32::from_be_bytes(bytes.try_into().unwrap()) as usize,
+            LengthEncoding::U32Le => u32::from_le_bytes(bytes.try_into().unwrap()) as usize,
+        }
+    }
+}
+
+/// Which bytes a candidate parse was run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForensicInput {
+    /// The payload after its flag byte, as posted.
+    Raw,
+    /// The brotli-decompressed payload.
+    Decompressed,
+}
+
+/// A segment guessed by the scanner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidateSegment {
+    /// Offset of the guessed header in the input.
+    pub offset: usize,
+    /// Bytes skipped before the header was found.
+    pub skipped: usize,
+    pub kind: u8,
+    pub len: usize,
+    pub encoding: LengthEncoding,
+    pub decoded: Result<Segment, BatchDecodeError>,
+}
+
+/// One way of reading the input as a sequence of segments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidateParse {
+    pub input: ForensicInput,
+    /// The length encoding this parse was restricted to; `None` tries every encoding at each position.
+    pub encoding: Option<LengthEncoding>,
+    pub segments: Vec<CandidateSegment>,
+    pub input_len: usize,
+    /// Bytes covered by segment headers and payloads.
+    pub bytes_covered: usize,
+    /// In `[0, 1]`: coverage × share of segments that decode to a known kind × resync penalty.
+    pub confidence: f64,
+}
+
+/// Result of `analyze`: the strict decoder's verdict and the candidate parses, best first.
+#[derive(Debug, Clone)]
+pub struct ForensicReport {
+    pub strict: Result<SequencerBatch, BatchDecodeError>,
+    pub candidates: Vec<CandidateParse>,
+}
+
+impl fmt::Display for ForensicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.strict {
+            Ok(batch) => writeln!(f, "strict decode: ok ({} segment(s))", batch.segments.len())?,
+            Err(e) => writeln!(f, "strict decode: {e}")?,
+        }
+        for (i, c) in self.candidates.iter().enumerate() {
+            let skipped: usize = c.segments.iter().map(|s| s.skipped).sum();
+            writeln!(
+                f,
+                "candidate {i}: confidence {:.3}, {:?} input ({} bytes), encoding {}, {} segment(s), {} bytes covered, {} byte(s) skipped",
+                c.confidence,
+                c.input,
+                c.input_len,
+                c.encoding.map_or("any".to_string(), |e| format!("{e:?}")),
+                c.segments.len(),
+                c.bytes_covered,
+                skipped
+            )?;
+            for s in &c.segments {
+                let mut decoded = match &s.decoded {
+                    Ok(segment) => format!("{segment:?}"),
+                    Err(e) => format!("error: {e}"),
+                };
+                decoded.truncate(120);
+                writeln!(
+                    f,
+                    "  offset {} (+{} skipped) kind {:#04x} len {} {:?}: {decoded}",
+                    s.offset, s.skipped, s.kind, s.len, s.encoding
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run the strict decoder and every heuristic scan over `payload` (flag byte included).
+pub fn analyze(header: BatchHeader, payload: &[u8]) -> ForensicReport {
+    let strict = SequencerBatch::decode(header, payload);
+
+    let mut inputs = Vec::new();
+    if let Some(raw) = payload.get(1..) {
+        inputs.push((ForensicInput::Raw, raw.to_vec()));
+        if let Ok(decompressed) = brotli_decompress(raw, MAX_DECOMPRESSED_LEN) {
+            inputs.push((ForensicInput::Decompressed, decompressed));
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for (input, data) in &inputs {
+        for encoding in LengthEncoding::ALL.map(Some).into_iter().chain([None]) {
+            let candidate = scan(*input, data, encoding);
+            if !candidate.segments.is_empty() {
+                candidates.push(candidate);
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    ForensicReport { strict, candidates }
+}
+
+fn plausible_kind(byte: u8) -> bool {
+    matches!(byte, 0x00 | 0x01 | 0x02 | 0x03 | 0x04 | 0x09)
+}
+
+/// A plausible `kind || length || payload` header at the start of `buf`.
+fn try_header(buf: &[u8], encoding: LengthEncoding) -> Option<(u8, usize, usize)> {
+    let width = encoding.width();
+    let kind = *buf.first()?;
+    let len = encoding.read(buf.get(1..1 + width)?);
+    if !plausible_kind(kind) || len == 0 || buf.len() - 1 - width < len {
+        return None;
+    }
+    Some((kind, len, 1 + width))
+}
+
+fn scan(input: ForensicInput, data: &[u8], encoding: Option<LengthEncoding>) -> CandidateParse {
+    let encodings: Vec<LengthEncoding> = match encoding {
+        Some(encoding) => vec![encoding],
+        None => LengthEncoding::ALL.to_vec(),
+    };
+
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let found = (0..=MAX_RESYNC_SKIP.min(data.len() - pos)).find_map(|skip| {
+            encodings
+                .iter()
+                .find_map(|&e| try_header(&data[pos + skip..], e).map(|header| (skip, e, header)))
+        });
+        let Some((skipped, encoding, (kind, len, header_len))) = found else {
+            break;
+        };
+        let offset = pos + skipped;
+        let body = &data[offset + header_len..offset + header_len + len];
+        let mut raw = Vec::with_capacity(1 + len);
+        raw.push(kind);
+        raw.extend_from_slice(body);
+        segments.push(CandidateSegment {
+            offset,
+            skipped,
+            kind,
+            len,
+            encoding,
+            decoded: decode_segment(segments.len(), offset, &raw),
+        });
+        pos = offset + header_len + len;
+    }
+
+    let bytes_covered = segments.iter().map(|s| s.len + 1 + s.encoding.width()).sum();
+    let confidence = confidence(&segments, bytes_covered, data.len());
+    CandidateParse { input, encoding, segments, input_len: data.len(), bytes_covered, confidence }
+}
+
+fn confidence(segments: &[CandidateSegment], bytes_covered: usize, input_len: usize) -> f64 {
+    if segments.is_empty() || input_len == 0 {
+        return 0.0;
+    }
+    let coverage = bytes_covered as f64 / input_len as f64;
+    let valid = segments
+        .iter()
+        .filter(|s| matches!(&s.decoded, Ok(segment) if !matches!(segment, Segment::Unknown { .. })))
+        .count();
+    let validity = valid as f64 / segments.len() as f64;
+    let skipped: usize = segments.iter().map(|s| s.skipped).sum();
+    let resync_penalty = 1.0 / (1.0 + skipped as f64 / 8.0);
+    coverage * validity * resync_penalty
+}
@@ -0,0 +1,102 @@
+// KZG commitments and proofs of EIP-4844 blobs.
+//
+// Loading a trusted setup is expensive, so one `KzgVerifier` is built at startup, either from a
+// trusted setup file or from the Ethereum setup embedded in c-kzg, and shared by every batch.
+
+use std::{path::Path, sync::Arc};
+
+use alloy::eips::eip4844::Bytes48;
+use c_kzg::{ethereum_kzg_settings_arc, Blob, KzgSettings};
+use thiserror::Error;
+
+use crate::nitro::blob::BYTES_PER_BLOB;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum KzgError {
+    #[error("cannot load the KZG trusted setup {path}: {reason}")]
+    Setup { path: String, reason: String },
+    #[error("blob {index} is {len} bytes, expected {BYTES_PER_BLOB}")]
+    BlobSize { index: usize, len: usize },
+    #[error("blob {index} is not a valid KZG blob: {reason}")]
+    InvalidBlob { index: usize, reason: String },
+    #[error("{blobs} blob(s) but {found} {what}")]
+    CountMismatch { blobs: usize, found: usize, what: &'static str },
+    #[error("blob KZG proof verification failed")]
+    InvalidProof,
+    #[error("KZG proof verification error: {0}")]
+    Verification(String),
+}
+
+/// Shared KZG settings: commitments and proof checks for blobs.
+#[derive(Clone)]
+pub struct KzgVerifier {
+    settings: Arc<KzgSettings>,
+}
+
+impl KzgVerifier {
+    /// The Ethereum mainnet trusted setup embedded in c-kzg.
+    pub fn embedded() -> Self {
+        Self { settings: ethereum_kzg_settings_arc(0) }
+    }
+
+    /// Load a trusted setup file in the c-kzg text format.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, KzgError> {
+        let path = path.as_ref();
+        let settings = KzgSettings::load_trusted_setup_file(path, 0)
+            .map_err(|e| KzgError::Setup { path: path.display().to_string(), reason: format!("{e:?}") })?;
+        Ok(Self { settings: Arc::new(settings) })
+    }
+
+    /// Copy raw blob bytes into KZG blobs, refusing anything that is not exactly one blob. A blob is
+    /// 128 KiB: they are filled in place on the heap rather than moved through the stack.
+    fn to_blobs<B: AsRef<[u8]>>(blobs: &[B]) -> Result<Vec<Blob>, KzgError> {
+        let mut kzg_blobs = vec![Blob::default(); blobs.len()];
+        for (index, (blob, data)) in kzg_blobs.iter_mut().zip(blobs).enumerate() {
+            let data = data.as_ref();
+            if data.len() != BYTES_PER_BLOB {
+                return Err(KzgError::BlobSize { index, len: data.len() });
+            }
+            blob.copy_from_slice(data);
+        }
+        Ok(kzg_blobs)
+    }
+
+    /// KZG commitment of each blob, in order.
+    pub fn commitments<B: AsRef<[u8]>>(&self, blobs: &[B]) -> Result<Vec<Bytes48>, KzgError> {
+        Self::to_blobs(blobs)?
+            .iter()
+            .enumerate()
+            .map(|(index, blob)| {
+                let commitment = self
+                    .settings
+                    .blob_to_kzg_commitment(blob)
+                    .map_err(|e| KzgError::InvalidBlob { index, reason: format!("{e:?}") })?;
+                Ok(Bytes48::from_slice(commitment.as_ref()))
+            })
+            .collect()
+    }
+
+    /// Check the blob KZG proofs a source supplied alongside the blobs (`verify_blob_kzg_proof_batch`).
+    pub fn verify_proofs<B: AsRef<[u8]>>(
+        &self,
+        blobs: &[B],
+        commitments: &[Bytes48],
+        proofs: &[Bytes48],
+    ) -> Result<(), KzgError> {
+        for (found, what) in [(commitments.len(), "commitment(s)"), (proofs.len(), "proof(s)")] {
+            if found != blobs.len() {
+                return Err(KzgError::CountMismatch { blobs: blobs.len(), found, what });
+            }
+        }
+        let kzg_blobs = Self::to_blobs(blobs)?;
+        let to_bytes48 = |bytes: &[Bytes48]| bytes.iter().map(|b| c_kzg::Bytes48::new(b.0)).collect::<Vec<_>>();
+        let verified = self
+            .settings
+            .verify_blob_kzg_proof_batch(&kzg_blobs, &to_bytes48(commitments), &to_bytes48(proofs))
+            .map_err(|e| KzgError::Verification(format!("{e:?}")))?;
+        if !verified {
+            return Err(KzgError::InvalidProof);
+        }
+        Ok(())
+    }
+}
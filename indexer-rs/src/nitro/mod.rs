@@ -0,0 +1,10 @@
+pub mod batch;
+pub mod batch_blobs;
+pub mod blob;
+pub mod blob_source;
+pub mod das;
+pub mod delayed;
+pub mod forensic;
+pub mod kzg;
+pub mod retryable;
+pub mod tx;
@@ -0,0 +1,259 @@
+// Decoding of `L1_MESSAGE_TYPE_SUBMIT_RETRYABLE_TX` payloads into `ArbitrumSubmitRetryableTx`.
+// Referencing to: https://github.com/OffchainLabs/nitro/blob/master/arbos/incomingmessage.go (parseSubmitRetryableMessage)
+// and https://github.com/OffchainLabs/go-ethereum/blob/master/core/types/arb_types.go (ArbitrumSubmitRetryableTx)
+//
+// When the submission executes, ArbOS schedules an auto-redeem: an `ArbitrumRetryTx` with nonce 0
+// that runs the retryable's call (arbos/tx_processor.go, StartTxHook). Its gas fee cap and refund
+// depend on the L2 base fee of the block the submission lands in, which the L1 message lacks.
+//
+// The payload is a sequence of 32-byte words: to, l2CallValue, deposit, maxSubmissionFee,
+// excessFeeRefundAddress, callValueRefundAddress, gasLimit, maxFeePerGas, dataLength, then data.
+
+use alloy::{
+    primitives::{keccak256, Address, Bytes, B256, U256},
+    rlp::{Encodable, Header},
+};
+use thiserror::Error;
+
+/// Transaction type byte of `ArbitrumSubmitRetryableTx`.
+pub const ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE: u8 = 0x69;
+
+/// Transaction type byte of `ArbitrumRetryTx`.
+pub const ARBITRUM_RETRY_TX_TYPE: u8 = 0x68;
+
+/// Largest retryable calldata Nitro accepts (`params.MaxTxSize`).
+pub const MAX_RETRYABLE_DATA_LEN: u64 = 128 * 1024;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RetryableError {
+    #[error("retryable payload truncated in {field}: need {needed} bytes, {available} available")]
+    Truncated { field: &'static str, needed: usize, available: usize },
+    #[error("retryable gas limit {0} does not fit in a u64")]
+    GasLimitTooLarge(U256),
+    #[error("retryable data length {0} exceeds {MAX_RETRYABLE_DATA_LEN} bytes")]
+    DataTooLarge(U256),
+}
+
+/// The fields of a Submit Retryable message, as posted through `Inbox.createRetryableTicket`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmitRetryable {
+    /// L2 destination; `None` (zero address on L1) for contract creation.
+    pub to: Option<Address>,
+    pub l2_call_value: U256,
+    pub deposit: U256,
+    pub max_submission_fee: U256,
+    pub excess_fee_refund_address: Address,
+    pub call_value_refund_address: Address,
+    pub gas_limit: u64,
+    pub max_fee_per_gas: U256,
+    pub data: Bytes,
+}
+
+/// `ArbitrumSubmitRetryableTx`, the L2 transaction created for a Submit Retryable message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmitRetryableTx {
+    pub chain_id: u64,
+    /// Delayed message index as a 32-byte word.
+    pub request_id: B256,
+    /// L1 sender as recorded by the Bridge (aliased for contracts).
+    pub from: Address,
+    pub l1_base_fee: U256,
+    pub retryable: SubmitRetryable,
+}
+
+impl SubmitRetryableTx {
+    /// L2 transaction hash: `keccak256(0x69 || rlp([chainId, requestId, from, l1BaseFee, deposit,
+    /// maxFeePerGas, gasLimit, to, l2CallValue, callValueRefundAddress, maxSubmissionFee,
+    /// excessFeeRefundAddress, data]))`.
+    pub fn hash(&self) -> B256 {
+        let r = &self.retryable;
+        // A nil `RetryTo` is encoded as the empty string.
+        let to: &[u8] = r.to.as_ref().map_or(&[], |to| to.as_slice());
+        let payload_length = self.chain_id.length()
+            + self.request_id.length()
+            + self.from.length()
+            + self.l1_base_fee.length()
+            + r.deposit.length()
+            + r.max_fee_per_gas.length()
+            + r.gas_limit.length()
+            + to.length()
+            + r.l2_call_value.length()
+            + r.call_value_refund_address.length()
+            + r.max_submission_fee.length()
+            + r.excess_fee_refund_address.length()
+            + r.data.length();
+
+        let mut out = vec![ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE];
+        Header { list: true, payload_length }.encode(&mut out);
+        self.chain_id.encode(&mut out);
+        self.request_id.encode(&mut out);
+        self.from.encode(&mut out);
+        self.l1_base_fee.encode(&mut out);
+        r.deposit.encode(&mut out);
+        r.max_fee_per_gas.encode(&mut out);
+        r.gas_limit.encode(&mut out);
+        to.encode(&mut out);
+        r.l2_call_value.encode(&mut out);
+        r.call_value_refund_address.encode(&mut out);
+        r.max_submission_fee.encode(&mut out);
+        r.excess_fee_refund_address.encode(&mut out);
+        r.data.encode(&mut out);
+        keccak256(out)
+    }
+
+    /// Retryable ticket ID. ArbOS keys the ticket by the hash of the submission transaction,
+    /// so the ID is also what `ArbRetryableTx.redeem` and the auto-redeem refer to; the redeem
+    /// that runs the call has its own hash, see [`Self::auto_redeem_hash`].
+    pub fn ticket_id(&self) -> B256 {
+        self.hash()
+    }
+
+    /// Submission fee ArbOS charges: `(1400 + 6 * len(data)) * l1BaseFee`.
+    pub fn submission_fee(&self) -> U256 {
+        self.l1_base_fee * U256::from(1400 + 6 * self.retryable.data.len())
+    }
+
+    /// The auto-redeem scheduled when the submission executes in an L2 block with base fee
+    /// `l2_base_fee`. ArbOS only schedules it if `max_fee_per_gas >= l2_base_fee`, the gas limit
+    /// is at least 21000 and the sender can pay for the gas.
+    pub fn auto_redeem(&self, l2_base_fee: U256) -> RetryTx {
+        let r = &self.retryable;
+        let submission_fee = self.submission_fee();
+        let gas = U256::from(r.gas_limit);
+
+        // Funds are withheld from the deposit in ArbOS' order; the withheld submission fee and
+        // gas cost are refundable by the redeem.
+        let mut available = r.deposit;
+        let mut take = |amount: U256| {
+            let taken = available.min(amount);
+            available -= taken;
+            taken
+        };
+        take(r.l2_call_value);
+        let withheld_submission_fee = take(submission_fee);
+        take(r.max_submission_fee.saturating_sub(submission_fee));
+        let withheld_gas_funds = take(l2_base_fee * gas);
+        take(r.max_fee_per_gas.saturating_sub(l2_base_fee) * gas);
+
+        RetryTx {
+            chain_id: self.chain_id,
+            nonce: 0,
+            from: self.from,
+            gas_fee_cap: l2_base_fee,
+            gas: r.gas_limit,
+            to: r.to,
+            value: r.l2_call_value,
+            data: r.data.clone(),
+            ticket_id: self.ticket_id(),
+            refund_to: r.excess_fee_refund_address,
+            max_refund: available + withheld_gas_funds + withheld_submission_fee,
+            submission_fee_refund: submission_fee,
+        }
+    }
+
+    /// Hash of the [`Self::auto_redeem`] transaction, i.e. of the L2 transaction executing the
+    /// retryable's call.
+    pub fn auto_redeem_hash(&self, l2_base_fee: U256) -> B256 {
+        self.auto_redeem(l2_base_fee).hash()
+    }
+}
+
+/// `ArbitrumRetryTx`, a redeem of a retryable ticket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryTx {
+    pub chain_id: u64,
+    /// Redeem count of the ticket; 0 for the auto-redeem.
+    pub nonce: u64,
+    pub from: Address,
+    pub gas_fee_cap: U256,
+    pub gas: u64,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Bytes,
+    pub ticket_id: B256,
+    pub refund_to: Address,
+    pub max_refund: U256,
+    pub submission_fee_refund: U256,
+}
+
+impl RetryTx {
+    /// L2 transaction hash: `keccak256(0x68 || rlp([chainId, nonce, from, gasFeeCap, gas, to,
+    /// value, data, ticketId, refundTo, maxRefund, submissionFeeRefund]))`.
+    pub fn hash(&self) -> B256 {
+        // A nil `To` is encoded as the empty string.
+        let to: &[u8] = self.to.as_ref().map_or(&[], |to| to.as_slice());
+        let payload_length = self.chain_id.length()
+            + self.nonce.length()
+            + self.from.length()
+            + self.gas_fee_cap.length()
+            + self.gas.length()
+            + to.length()
+            + self.value.length()
+            + self.data.length()
+            + self.ticket_id.length()
+            + self.refund_to.length()
+            + self.max_refund.length()
+            + self.submission_fee_refund.length();
+
+        let mut out = vec![ARBITRUM_RETRY_TX_TYPE];
+        Header { list: true, payload_length }.encode(&mut out);
+        self.chain_id.encode(&mut out);
+        self.nonce.encode(&mut out);
+        self.from.encode(&mut out);
+        self.gas_fee_cap.encode(&mut out);
+        self.gas.encode(&mut out);
+        to.encode(&mut out);
+        self.value.encode(&mut out);
+        self.data.encode(&mut out);
+        self.ticket_id.encode(&mut out);
+        self.refund_to.encode(&mut out);
+        self.max_refund.encode(&mut out);
+        self.submission_fee_refund.encode(&mut out);
+        keccak256(out)
+    }
+}
+
+/// Decode the payload of a Submit Retryable message.
+pub fn decode_submit_retryable(data: &[u8]) -> Result<SubmitRetryable, RetryableError> {
+    let mut rest = data;
+    let mut word = |field: &'static str| -> Result<U256, RetryableError> {
+        let Some((head, tail)) = rest.split_first_chunk::<32>() else {
+            return Err(RetryableError::Truncated { field, needed: 32, available: rest.len() });
+        };
+        rest = tail;
+        Ok(U256::from_be_bytes(*head))
+    };
+    // Addresses are right-aligned in their word; the upper bytes are ignored, as in Nitro.
+    let address = |value: U256| Address::from_word(value.into());
+
+    let to = address(word("to")?);
+    let l2_call_value = word("l2CallValue")?;
+    let deposit = word("deposit")?;
+    let max_submission_fee = word("maxSubmissionFee")?;
+    let excess_fee_refund_address = address(word("excessFeeRefundAddress")?);
+    let call_value_refund_address = address(word("callValueRefundAddress")?);
+    let gas_limit = word("gasLimit")?;
+    let max_fee_per_gas = word("maxFeePerGas")?;
+    let data_length = word("dataLength")?;
+
+    let gas_limit = u64::try_from(gas_limit).map_err(|_| RetryableError::GasLimitTooLarge(gas_limit))?;
+    let len = match u64::try_from(data_length) {
+        Ok(len) if len <= MAX_RETRYABLE_DATA_LEN => len as usize,
+        _ => return Err(RetryableError::DataTooLarge(data_length)),
+    };
+    if rest.len() < len {
+        return Err(RetryableError::Truncated { field: "data", needed: len, available: rest.len() });
+    }
+
+    Ok(SubmitRetryable {
+        to: (to != Address::ZERO).then_some(to),
+        l2_call_value,
+        deposit,
+        max_submission_fee,
+        excess_fee_refund_address,
+        call_value_refund_address,
+        gas_limit,
+        max_fee_per_gas,
+        data: Bytes::copy_from_slice(&rest[..len]),
+    })
+}
@@ -0,0 +1,77 @@
+// Decoding of the signed transactions carried by `L2_MESSAGE_KIND_SIGNED_TX` messages.
+// Nitro accepts legacy (with or without EIP-155 replay protection), EIP-2930, EIP-1559 and
+// EIP-7702 transactions here; blob transactions and Arbitrum's internal types are rejected.
+
+use alloy::{
+    consensus::{transaction::SignerRecoverable, Transaction, TxEnvelope, TxType},
+    eips::eip2718::{Decodable2718, Eip2718Error},
+    primitives::{Address, Bytes, B256, U256},
+};
+use thiserror::Error;
+
+use crate::nitro::batch::SequencerBatch;
+
+#[derive(Debug, Error)]
+pub enum TxDecodeError {
+    #[error("empty transaction")]
+    Empty,
+    #[error("invalid transaction encoding: {0}")]
+    Encoding(#[from] Eip2718Error),
+    #[error("{0} trailing byte(s) after the transaction")]
+    TrailingBytes(usize),
+    #[error("transaction type {0} is not allowed in a SignedTx message")]
+    UnsupportedType(TxType),
+    #[error("sender recovery failed: {0}")]
+    Recovery(#[from] alloy::consensus::crypto::RecoveryError),
+}
+
+/// A decoded L2 transaction with its recovered sender and core fields.
+#[derive(Debug, Clone)]
+pub struct L2Transaction {
+    pub hash: B256,
+    pub tx_type: TxType,
+    pub chain_id: Option<u64>,
+    pub from: Address,
+    /// `None` for contract creation.
+    pub to: Option<Address>,
+    pub value: U256,
+    pub nonce: u64,
+    pub input: Bytes,
+    pub envelope: TxEnvelope,
+}
+
+/// Decode the body of a SignedTx message into a `TxEnvelope`, recovering the sender.
+pub fn decode_signed_tx(raw: &[u8]) -> Result<L2Transaction, TxDecodeError> {
+    if raw.is_empty() {
+        return Err(TxDecodeError::Empty);
+    }
+    let mut buf = raw;
+    let envelope = TxEnvelope::decode_2718(&mut buf)?;
+    if !buf.is_empty() {
+        return Err(TxDecodeError::TrailingBytes(buf.len()));
+    }
+    let tx_type = envelope.tx_type();
+    if tx_type == TxType::Eip4844 {
+        return Err(TxDecodeError::UnsupportedType(tx_type));
+    }
+
+    let from = envelope.recover_signer()?;
+    Ok(L2Transaction {
+        hash: *envelope.tx_hash(),
+        tx_type,
+        chain_id: envelope.chain_id(),
+        from,
+        to: envelope.to(),
+        value: envelope.value(),
+        nonce: envelope.nonce(),
+        input: envelope.input().clone(),
+        envelope,
+    })
+}
+
+impl SequencerBatch {
+    /// Decode every signed transaction of the batch, in order (nested batches flattened).
+    pub fn transactions(&self) -> impl Iterator<Item = Result<L2Transaction, TxDecodeError>> + '_ {
+        self.signed_txs().into_iter().map(|raw| decode_signed_tx(raw))
+    }
+}
@@ -0,0 +1,215 @@
+// Resilient L1 RPC access.
+//
+// `FailoverTransport` sits below the provider as a tower service over an ordered list of
+// endpoints. A request goes to the first healthy endpoint; rate limits (429), 5xx responses and
+// connection errors move it to the next one, and once every endpoint has failed the request is
+// retried after an exponential backoff with jitter. Endpoints failing repeatedly are marked
+// unhealthy for a cooldown and tried last. JSON-RPC errors such as reverts or an oversized
+// `eth_getLogs` range are answers, not endpoint failures, and are returned as-is.
+
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use alloy::{
+    providers::{DynProvider, Provider, ProviderBuilder},
+    rpc::{
+        client::{BuiltInConnectionString, RpcClient},
+        json_rpc::{RequestPacket, ResponsePacket},
+    },
+    transports::{BoxTransport, RpcError, TransportError, TransportErrorKind, TransportFut},
+};
+use tower::Service;
+
+/// Backoff and health thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Rounds over every endpoint after the first one, before giving up.
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Consecutive failures after which an endpoint is unhealthy.
+    pub failure_threshold: u32,
+    /// How long an unhealthy endpoint is tried last.
+    pub cooldown: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(30),
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry `attempt` (0-based): `initial_backoff * 2^attempt` capped at
+    /// `max_backoff`, with a random jitter taking it down to as low as half of that.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self.initial_backoff.saturating_mul(2u32.saturating_pow(attempt)).min(self.max_backoff);
+        let ceiling_ms = ceiling.as_millis() as u64;
+        Duration::from_millis(rand::random_range(ceiling_ms / 2..=ceiling_ms))
+    }
+}
+
+/// Whether `error` says something about the endpoint (rate limited, down, unreachable) rather than
+/// about the request, so that another endpoint or a later retry may succeed.
+pub fn is_endpoint_failure(error: &TransportError) -> bool {
+    match error {
+        RpcError::Transport(TransportErrorKind::HttpError(http)) => http.status == 429 || http.status >= 500,
+        RpcError::Transport(_) => true,
+        RpcError::ErrorResp(payload) => payload.is_retry_err(),
+        RpcError::NullResp => true,
+        _ => false,
+    }
+}
+
+/// Request counters and failure state of one endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointHealth {
+    pub successes: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    pub unhealthy_until: Option<Instant>,
+}
+
+impl EndpointHealth {
+    pub fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until.is_none_or(|until| now >= until)
+    }
+
+    fn record_success(&mut self) {
+        self.successes += 1;
+        self.consecutive_failures = 0;
+        self.unhealthy_until = None;
+    }
+
+    fn record_failure(&mut self, policy: &RetryPolicy, now: Instant) {
+        self.failures += 1;
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= policy.failure_threshold {
+            self.unhealthy_until = Some(now + policy.cooldown);
+        }
+    }
+}
+
+struct Endpoint {
+    url: String,
+    transport: BoxTransport,
+    health: Mutex<EndpointHealth>,
+}
+
+/// Transport spreading requests over ordered fallback endpoints; see the module comment.
+#[derive(Clone)]
+pub struct FailoverTransport {
+    endpoints: Arc<[Endpoint]>,
+    policy: RetryPolicy,
+}
+
+impl FailoverTransport {
+    /// `endpoints` in order of preference, each with a label (usually its URL) for logs.
+    pub fn new(endpoints: Vec<(String, BoxTransport)>, policy: RetryPolicy) -> Self {
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(url, transport)| Endpoint { url, transport, health: Mutex::new(EndpointHealth::default()) })
+            .collect();
+        Self { endpoints, policy }
+    }
+
+    /// Connect to every HTTP or WebSocket URL in `urls`. Endpoints that cannot be reached now are
+    /// skipped; it fails only if none can.
+    pub async fn connect(urls: &[String], policy: RetryPolicy) -> Result<Self, TransportError> {
+        let mut endpoints = Vec::new();
+        let mut last_error = TransportErrorKind::custom_str("no RPC endpoint configured");
+        for url in urls {
+            match url.parse::<BuiltInConnectionString>()?.connect_boxed().await {
+                Ok(transport) => endpoints.push((url.clone(), transport)),
+                Err(e) => {
+                    println!("Cannot connect to RPC endpoint {url}: {e}");
+                    last_error = e;
+                }
+            }
+        }
+        if endpoints.is_empty() {
+            return Err(last_error);
+        }
+        Ok(Self::new(endpoints, policy))
+    }
+
+    /// Wrap the transport in a provider.
+    pub fn into_provider(self) -> DynProvider {
+        ProviderBuilder::new().connect_client(RpcClient::new(self, false)).erased()
+    }
+
+    /// Label and health of every endpoint, in order of preference.
+    pub fn health(&self) -> Vec<(String, EndpointHealth)> {
+        self.endpoints.iter().map(|e| (e.url.clone(), e.health.lock().unwrap().clone())).collect()
+    }
+
+    /// Healthy endpoints first, each group in configured order.
+    fn candidates(&self) -> Vec<&Endpoint> {
+        let now = Instant::now();
+        let (mut healthy, unhealthy): (Vec<_>, Vec<_>) =
+            self.endpoints.iter().partition(|e| e.health.lock().unwrap().is_healthy(now));
+        healthy.extend(unhealthy);
+        healthy
+    }
+
+    async fn dispatch(self, request: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let mut attempt = 0;
+        loop {
+            let mut last_error = None;
+            for endpoint in self.candidates() {
+                let result = endpoint.transport.clone().call(request.clone()).await;
+                // A single request answered with a rate-limit error payload is a failure of the endpoint too.
+                let result = match result {
+                    Ok(ResponsePacket::Single(response)) => match response.payload.as_error() {
+                        Some(payload) if payload.is_retry_err() => Err(RpcError::ErrorResp(payload.clone())),
+                        _ => Ok(ResponsePacket::Single(response)),
+                    },
+                    other => other,
+                };
+                match result {
+                    Ok(response) => {
+                        endpoint.health.lock().unwrap().record_success();
+                        return Ok(response);
+                    }
+                    Err(e) if is_endpoint_failure(&e) => {
+                        println!("RPC endpoint {} failed: {e}", endpoint.url);
+                        endpoint.health.lock().unwrap().record_failure(&self.policy, Instant::now());
+                        last_error = Some(e);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            let error = last_error.unwrap_or_else(|| TransportErrorKind::custom_str("no RPC endpoint configured"));
+            if attempt >= self.policy.max_retries {
+                return Err(error);
+            }
+            let delay = self.policy.backoff(attempt);
+            println!("Every RPC endpoint failed; retrying in {delay:?}");
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl Service<RequestPacket> for FailoverTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().dispatch(request))
+    }
+}
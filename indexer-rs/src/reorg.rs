@@ -0,0 +1,178 @@
+// L1 reorg tracking for indexed batches.
+//
+// A batch is tentative until its L1 block is `confirmations` deep, then becomes safe and finalized
+// as the node's `safe` / `finalized` tags pass it. A removed log, the node's signal that the block
+// holding it was reorged out, orphans the batch and every batch indexed after it, and yields the
+// cursor to roll back to. Only confirmed progress is written to the durable checkpoint.
+
+use std::{collections::BTreeMap, fmt};
+
+use alloy::{eips::BlockNumberOrTag, primitives::B256, providers::Provider, transports::TransportError};
+
+use crate::checkpoint::Checkpoint;
+
+/// L1 blocks on top of a batch before it counts as confirmed.
+pub const DEFAULT_CONFIRMATION_DEPTH: u64 = 12;
+
+/// Finality of a batch's L1 block, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BatchStatus {
+    Tentative,
+    /// At least `confirmations` blocks deep.
+    Confirmed,
+    /// At or below the `safe` block.
+    Safe,
+    /// At or below the `finalized` block; it can no longer be reorged out.
+    Finalized,
+}
+
+impl fmt::Display for BatchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BatchStatus::Tentative => "tentative",
+            BatchStatus::Confirmed => "confirmed",
+            BatchStatus::Safe => "safe",
+            BatchStatus::Finalized => "finalized",
+        })
+    }
+}
+
+/// The L1 head and the `safe` / `finalized` blocks, when the node reports them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainHeads {
+    pub head: u64,
+    pub safe: Option<u64>,
+    pub finalized: Option<u64>,
+}
+
+impl ChainHeads {
+    pub async fn fetch<P: Provider>(provider: &P) -> Result<Self, TransportError> {
+        let head = provider.get_block_number().await?;
+        let safe = provider.get_block_by_number(BlockNumberOrTag::Safe).await?;
+        let finalized = provider.get_block_by_number(BlockNumberOrTag::Finalized).await?;
+        Ok(Self {
+            head,
+            safe: safe.map(|block| block.header.number),
+            finalized: finalized.map(|block| block.header.number),
+        })
+    }
+}
+
+/// A batch indexed but not finalized yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedBatch {
+    pub checkpoint: Checkpoint,
+    pub block_hash: B256,
+    pub status: BatchStatus,
+    /// The cursor before this batch, restored if it is orphaned.
+    pub previous: Option<Checkpoint>,
+}
+
+/// What a removed log undoes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rollback {
+    /// The orphaned batches, in sequence order.
+    pub orphaned: Vec<TrackedBatch>,
+    /// The cursor to resume from.
+    pub cursor: Option<Checkpoint>,
+    /// First L1 block whose derived data is no longer valid.
+    pub from_block: u64,
+}
+
+/// Finality of the batches indexed since the last finalized one.
+#[derive(Debug, Clone)]
+pub struct ReorgTracker {
+    confirmations: u64,
+    heads: Option<ChainHeads>,
+    batches: BTreeMap<u64, TrackedBatch>,
+    // The newest batch that left the tracker, or the checkpoint the indexer resumed from.
+    base: Option<Checkpoint>,
+}
+
+impl ReorgTracker {
+    /// `resume` is the durable checkpoint the indexer started from.
+    pub fn new(confirmations: u64, resume: Option<Checkpoint>) -> Self {
+        Self { confirmations, heads: None, batches: BTreeMap::new(), base: resume }
+    }
+
+    pub fn confirmations(&self) -> u64 {
+        self.confirmations
+    }
+
+    pub fn get(&self, batch_sequence_number: u64) -> Option<&TrackedBatch> {
+        self.batches.get(&batch_sequence_number)
+    }
+
+    /// The newest batch whose status is at least confirmed, i.e. what may be written to the
+    /// durable checkpoint.
+    pub fn durable(&self) -> Option<Checkpoint> {
+        self.batches
+            .values()
+            .rev()
+            .find(|batch| batch.status >= BatchStatus::Confirmed)
+            .map(|batch| batch.checkpoint)
+            .or(self.base)
+    }
+
+    /// Track a newly indexed batch; `previous` is the cursor before it.
+    pub fn record(&mut self, checkpoint: Checkpoint, block_hash: B256, previous: Option<Checkpoint>) -> BatchStatus {
+        let status = status_at(self.heads, self.confirmations, checkpoint.l1_block);
+        self.batches
+            .insert(checkpoint.batch_sequence_number, TrackedBatch { checkpoint, block_hash, status, previous });
+        self.prune_finalized();
+        status
+    }
+
+    /// Apply new chain heads, returning the batches whose status changed. Statuses only move
+    /// forward; finalized batches stop being tracked.
+    pub fn update(&mut self, heads: ChainHeads) -> Vec<(u64, BatchStatus)> {
+        self.heads = Some(heads);
+        let mut promoted = Vec::new();
+        for (seq, batch) in &mut self.batches {
+            let status = status_at(self.heads, self.confirmations, batch.checkpoint.l1_block);
+            if status > batch.status {
+                batch.status = status;
+                promoted.push((*seq, status));
+            }
+        }
+        self.prune_finalized();
+        promoted
+    }
+
+    fn prune_finalized(&mut self) {
+        while let Some(entry) = self.batches.first_entry() {
+            if entry.get().status != BatchStatus::Finalized {
+                break;
+            }
+            self.base = Some(entry.remove().checkpoint);
+        }
+    }
+
+    /// Handle the removed log of batch `batch_sequence_number` in block `block_hash`: orphan it and
+    /// every batch indexed after it. `None` when the batch is not tracked from that block.
+    pub fn remove(&mut self, batch_sequence_number: u64, block_hash: B256) -> Option<Rollback> {
+        let batch = self.batches.get(&batch_sequence_number)?;
+        if batch.block_hash != block_hash {
+            return None;
+        }
+        let cursor = batch.previous;
+        let from_block = batch.checkpoint.l1_block;
+        let orphaned = self.batches.split_off(&batch_sequence_number).into_values().collect();
+        Some(Rollback { orphaned, cursor, from_block })
+    }
+}
+
+fn status_at(heads: Option<ChainHeads>, confirmations: u64, l1_block: u64) -> BatchStatus {
+    let Some(heads) = heads else {
+        return BatchStatus::Tentative;
+    };
+    if heads.finalized.is_some_and(|finalized| l1_block <= finalized) {
+        BatchStatus::Finalized
+    } else if heads.safe.is_some_and(|safe| l1_block <= safe) {
+        BatchStatus::Safe
+    } else if heads.head.saturating_sub(l1_block) >= confirmations {
+        BatchStatus::Confirmed
+    } else {
+        BatchStatus::Tentative
+    }
+}
@@ -0,0 +1,179 @@
+// SequencerInbox bindings and retrieval of the batch data for every `BatchDataLocation`.
+// Referencing to: https://github.com/OffchainLabs/nitro-contracts/blob/main/src/bridge/SequencerInbox.sol
+
+use alloy::{
+    primitives::{Address, Bytes, B256, U256},
+    providers::Provider,
+    rpc::types::Log,
+    sol,
+    sol_types::{SolEvent, SolInterface},
+    consensus::Transaction,
+    transports::TransportError,
+};
+use thiserror::Error;
+
+use crate::nitro::batch::BatchHeader;
+
+sol! {
+    #[allow(missing_docs, clippy::too_many_arguments)]
+    #[sol(rpc)]
+    SequencerInbox,
+    "src/abi/SequencerInbox.json"
+}
+
+sol! {
+    #[derive(Debug)]
+    struct TimeBounds {
+        uint64 minTimestamp;
+        uint64 maxTimestamp;
+        uint64 minBlockNumber;
+        uint64 maxBlockNumber;
+    }
+
+    #[derive(Debug)]
+    enum BatchDataLocation {
+        /// @notice The data can be found in the transaction call data
+        TxInput,
+        /// @notice The data can be found in an event emitted during the transaction
+        SeparateBatchEvent,
+        /// @notice This batch contains no data
+        NoData,
+        /// @notice The data can be found in the 4844 data blobs on this transaction
+        Blob
+    }
+
+    #[derive(Debug)]
+    event SequencerBatchDelivered(
+        uint256 indexed batchSequenceNumber,
+        bytes32 indexed beforeAcc,
+        bytes32 indexed afterAcc,
+        bytes32 delayedAcc,
+        uint256 afterDelayedMessagesRead,
+        TimeBounds timeBounds,
+        BatchDataLocation dataLocation
+    );
+}
+
+#[derive(Debug, Error)]
+pub enum BatchDataError {
+    #[error("batch transaction {0} not found")]
+    TransactionNotFound(B256),
+    #[error("receipt of batch transaction {0} not found")]
+    ReceiptNotFound(B256),
+    #[error("calldata is not a SequencerInbox batch submission (selector 0x{0})")]
+    UnsupportedCall(String),
+    #[error("calldata carries batch {found} but the event reported batch {expected}")]
+    SequenceNumberMismatch { expected: U256, found: U256 },
+    #[error("no SequencerBatchData event for batch {0} in the batch transaction")]
+    MissingBatchDataEvent(U256),
+    #[error("batch transaction {0} carries no blobs")]
+    MissingBlobs(B256),
+    #[error("unknown batch data location")]
+    UnknownLocation,
+    #[error("ABI decoding failed: {0}")]
+    Abi(#[from] alloy::sol_types::Error),
+    #[error(transparent)]
+    Rpc(#[from] TransportError),
+}
+
+/// Where the payload of a batch lives once its `BatchDataLocation` has been resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchPayload {
+    /// Payload posted in calldata or in a `SequencerBatchData` event (without the 40-byte header).
+    Data(Bytes),
+    /// Payload lives in the blobs with these versioned hashes, in order.
+    Blobs(Vec<B256>),
+    /// The batch has no data.
+    NoData,
+}
+
+/// The 40-byte header the SequencerInbox hashed for this batch, rebuilt from the event.
+pub fn batch_header(event: &SequencerBatchDelivered) -> BatchHeader {
+    BatchHeader {
+        min_timestamp: event.timeBounds.minTimestamp,
+        max_timestamp: event.timeBounds.maxTimestamp,
+        min_l1_block: event.timeBounds.minBlockNumber,
+        max_l1_block: event.timeBounds.maxBlockNumber,
+        after_delayed_messages: event.afterDelayedMessagesRead.saturating_to(),
+    }
+}
+
+/// Extract `(sequenceNumber, data)` from the calldata of a batch-posting call.
+pub fn decode_batch_calldata(input: &[u8]) -> Result<(U256, Bytes), BatchDataError> {
+    use SequencerInbox::SequencerInboxCalls as Calls;
+
+    let call = Calls::abi_decode(input).map_err(|_| {
+        BatchDataError::UnsupportedCall(alloy::hex::encode(&input[..4.min(input.len())]))
+    })?;
+    let decoded = match call {
+        Calls::addSequencerL2BatchFromOrigin_0(c) => (c._0, c._1),
+        Calls::addSequencerL2BatchFromOrigin_1(c) => (c.sequenceNumber, c.data),
+        Calls::addSequencerL2BatchFromOriginDelayProof(c) => (c.sequenceNumber, c.data),
+        Calls::addSequencerL2Batch(c) => (c.sequenceNumber, c.data),
+        Calls::addSequencerL2BatchDelayProof(c) => (c.sequenceNumber, c.data),
+        _ => {
+            return Err(BatchDataError::UnsupportedCall(alloy::hex::encode(&input[..4])));
+        }
+    };
+    Ok(decoded)
+}
+
+/// Find the `SequencerBatchData` event of `sequence_number` emitted by `inbox`.
+pub fn find_batch_data_event(logs: &[Log], inbox: Address, sequence_number: U256) -> Result<Bytes, BatchDataError> {
+    for log in logs {
+        if log.address() != inbox || log.topics().first() != Some(&SequencerInbox::SequencerBatchData::SIGNATURE_HASH) {
+            continue;
+        }
+        let event = SequencerInbox::SequencerBatchData::decode_log(&log.inner)?;
+        if event.batchSequenceNumber == sequence_number {
+            return Ok(event.data.data.clone());
+        }
+    }
+    Err(BatchDataError::MissingBatchDataEvent(sequence_number))
+}
+
+/// Resolve the payload of the batch delivered by `event` in transaction `tx_hash`.
+pub async fn fetch_batch_payload<P: Provider>(
+    provider: &P,
+    inbox: Address,
+    tx_hash: B256,
+    event: &SequencerBatchDelivered,
+) -> Result<BatchPayload, BatchDataError> {
+    match event.dataLocation {
+        BatchDataLocation::TxInput => {
+            let tx = provider
+                .get_transaction_by_hash(tx_hash)
+                .await?
+                .ok_or(BatchDataError::TransactionNotFound(tx_hash))?;
+            let (sequence_number, data) = decode_batch_calldata(tx.input())?;
+            if sequence_number != event.batchSequenceNumber {
+                return Err(BatchDataError::SequenceNumberMismatch {
+                    expected: event.batchSequenceNumber,
+                    found: sequence_number,
+                });
+            }
+            Ok(BatchPayload::Data(data))
+        }
+        BatchDataLocation::SeparateBatchEvent => {
+            let receipt = provider
+                .get_transaction_receipt(tx_hash)
+                .await?
+                .ok_or(BatchDataError::ReceiptNotFound(tx_hash))?;
+            let data = find_batch_data_event(receipt.inner.logs(), inbox, event.batchSequenceNumber)?;
+            Ok(BatchPayload::Data(data))
+        }
+        BatchDataLocation::NoData => Ok(BatchPayload::NoData),
+        BatchDataLocation::Blob => {
+            let tx = provider
+                .get_transaction_by_hash(tx_hash)
+                .await?
+                .ok_or(BatchDataError::TransactionNotFound(tx_hash))?;
+            match tx.blob_versioned_hashes() {
+                Some(hashes) if !hashes.is_empty() => Ok(BatchPayload::Blobs(hashes.to_vec())),
+                _ => Err(BatchDataError::MissingBlobs(tx_hash)),
+            }
+        }
+        _ => Err(BatchDataError::UnknownLocation),
+    }
+}
+
@@ -0,0 +1,103 @@
+// Registry decoders of the Uniswap v3 periphery contracts on Arbitrum One. Forks that keep the
+// periphery ABI (SushiSwap v3, ...) are reached through selector fallback; their factory and pool
+// creation code are unknown, so no pool addresses are derived for them.
+
+use alloy::primitives::{Address, Selector};
+
+use crate::{
+    decoder::{CalldataDecoder, DecodedCall, DecoderError, Route},
+    uniswap::{
+        path::{PoolFactory, UNISWAP_V3_FACTORY},
+        position_manager::{decode_position_manager_call, INonfungiblePositionManager, POSITION_MANAGER},
+        router::{
+            decode_router_call, IMulticall, IMulticallExtended, ISwapRouter, IV3SwapRouter, SWAP_ROUTER, SWAP_ROUTER_02,
+        },
+        universal_router::{decode_universal_router_call, IUniversalRouter, UNIVERSAL_ROUTERS},
+    },
+};
+
+fn selectors(lists: &[&[[u8; 4]]]) -> Vec<Selector> {
+    lists.iter().flat_map(|list| list.iter()).map(|&selector| Selector::from(selector)).collect()
+}
+
+/// SwapRouter and SwapRouter02 swaps.
+pub struct SwapRouterDecoder;
+
+impl CalldataDecoder for SwapRouterDecoder {
+    fn name(&self) -> &str {
+        "uniswap-v3-router"
+    }
+
+    fn addresses(&self) -> &[Address] {
+        &[SWAP_ROUTER, SWAP_ROUTER_02]
+    }
+
+    fn selectors(&self) -> Vec<Selector> {
+        selectors(&[
+            ISwapRouter::ISwapRouterCalls::SELECTORS,
+            IV3SwapRouter::IV3SwapRouterCalls::SELECTORS,
+            IMulticall::IMulticallCalls::SELECTORS,
+            IMulticallExtended::IMulticallExtendedCalls::SELECTORS,
+        ])
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<DecodedCall>, DecoderError> {
+        Ok(decode_router_call(input)?.into_iter().map(DecodedCall::Swap).collect())
+    }
+}
+
+/// NonfungiblePositionManager liquidity actions.
+pub struct PositionManagerDecoder;
+
+impl CalldataDecoder for PositionManagerDecoder {
+    fn name(&self) -> &str {
+        "uniswap-v3-positions"
+    }
+
+    fn addresses(&self) -> &[Address] {
+        &[POSITION_MANAGER]
+    }
+
+    fn selectors(&self) -> Vec<Selector> {
+        selectors(&[INonfungiblePositionManager::INonfungiblePositionManagerCalls::SELECTORS])
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<DecodedCall>, DecoderError> {
+        Ok(decode_position_manager_call(input)?.into_iter().map(DecodedCall::Liquidity).collect())
+    }
+}
+
+/// Universal Router command streams.
+pub struct UniversalRouterDecoder;
+
+impl CalldataDecoder for UniversalRouterDecoder {
+    fn name(&self) -> &str {
+        "uniswap-universal-router"
+    }
+
+    fn addresses(&self) -> &[Address] {
+        &UNIVERSAL_ROUTERS
+    }
+
+    fn selectors(&self) -> Vec<Selector> {
+        selectors(&[IUniversalRouter::IUniversalRouterCalls::SELECTORS])
+    }
+
+    fn decode(&self, input: &[u8]) -> Result<Vec<DecodedCall>, DecoderError> {
+        Ok(decode_universal_router_call(input)?.into_iter().map(DecodedCall::UniversalRouter).collect())
+    }
+}
+
+/// Every Uniswap decoder, for [`DecoderRegistry::new`](crate::decoder::DecoderRegistry::new).
+pub fn decoders() -> Vec<Box<dyn CalldataDecoder>> {
+    vec![Box::new(SwapRouterDecoder), Box::new(PositionManagerDecoder), Box::new(UniversalRouterDecoder)]
+}
+
+/// The factory of the pools behind calls the registry routed by `route` to a Uniswap decoder:
+/// Uniswap's on its deployments, unknown on selector fallback.
+pub fn pool_factory(route: Route) -> Option<PoolFactory> {
+    match route {
+        Route::Deployment => Some(UNISWAP_V3_FACTORY),
+        Route::Selector => None,
+    }
+}
@@ -0,0 +1,5 @@
+pub mod decoder;
+pub mod path;
+pub mod position_manager;
+pub mod router;
+pub mod universal_router;
@@ -0,0 +1,132 @@
+// Uniswap v3 encoded swap paths.
+// Referencing to: https://github.com/Uniswap/v3-periphery/blob/main/contracts/libraries/Path.sol
+//
+// A path is `token (20) | fee (3, BE) | token (20) | ...`: one fee tier between every pair of
+// tokens. `exactInput` paths run from the token paid in to the token received; `exactOutput`
+// paths are encoded in reverse.
+//
+// Each hop is one pool. Pools are deployed by the factory with CREATE2, salted with
+// `keccak256(abi.encode(token0, token1, fee))` over the sorted token pair, so a hop's pool address
+// follows from the path and the factory. Forks deploy their own factory and usually their own pool
+// creation code, so neither is assumed here.
+// Referencing to: https://github.com/Uniswap/v3-periphery/blob/main/contracts/libraries/PoolAddress.sol
+
+use alloy::{
+    primitives::{address, aliases::U24, b256, keccak256, Address, Bytes, B256},
+    sol_types::SolValue,
+};
+use thiserror::Error;
+
+pub const ADDR_SIZE: usize = 20;
+pub const FEE_SIZE: usize = 3;
+/// One hop more in the path.
+pub const NEXT_OFFSET: usize = ADDR_SIZE + FEE_SIZE;
+
+/// UniswapV3Factory, at the same address on Ethereum and Arbitrum One.
+pub const V3_FACTORY: Address = address!("0x1F98431c8aD98523631AE4a59f267346ea31F984");
+/// `keccak256` of the UniswapV3Pool creation code, as in `PoolAddress.sol`.
+pub const POOL_INIT_CODE_HASH: B256 = b256!("0xe34f199b19b2b4f47f68442619d555527d244f78a3297ea89325f843f87b8b54");
+/// The Uniswap v3 deployment.
+pub const UNISWAP_V3_FACTORY: PoolFactory = PoolFactory { address: V3_FACTORY, init_code_hash: POOL_INIT_CODE_HASH };
+/// Fee tiers enabled on the factory, in hundredths of a bip (0.01%, 0.05%, 0.3%, 1%).
+pub const FEE_TIERS: [u32; 4] = [100, 500, 3000, 10000];
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum PathError {
+    #[error("path of {0} bytes is not `token (fee token)+`")]
+    InvalidLength(usize),
+    #[error("fee {0} is not an enabled v3 fee tier")]
+    InvalidFee(u32),
+    #[error("path of {tokens} token(s) and {fees} fee(s) is not `token (fee token)+`")]
+    InvalidHops { tokens: usize, fees: usize },
+}
+
+/// `fee` if it is one of [`FEE_TIERS`].
+pub fn check_fee(fee: u32) -> Result<u32, PathError> {
+    if FEE_TIERS.contains(&fee) {
+        Ok(fee)
+    } else {
+        Err(PathError::InvalidFee(fee))
+    }
+}
+
+/// A v3 factory and the creation code hash of the pools it deploys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolFactory {
+    pub address: Address,
+    pub init_code_hash: B256,
+}
+
+impl PoolFactory {
+    /// Address of the pool of `token_a` and `token_b` (either order) at `fee`.
+    pub fn pool_address(&self, token_a: Address, token_b: Address, fee: u32) -> Address {
+        let (token0, token1) = if token_a < token_b { (token_a, token_b) } else { (token_b, token_a) };
+        let salt = keccak256((token0, token1, U24::from(fee)).abi_encode());
+        self.address.create2(salt, self.init_code_hash)
+    }
+}
+
+/// One pool of a path, in swap direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hop {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub fee: u32,
+    /// The pool swapped through.
+    pub pool: Address,
+}
+
+impl Hop {
+    pub fn new(token_in: Address, token_out: Address, fee: u32, factory: &PoolFactory) -> Self {
+        Self { token_in, token_out, fee, pool: factory.pool_address(token_in, token_out, fee) }
+    }
+}
+
+/// A decoded swap path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct V3Path {
+    pub tokens: Vec<Address>,
+    /// Fee tier of each hop, in hundredths of a bip; one fewer than `tokens`.
+    pub fees: Vec<u32>,
+}
+
+impl V3Path {
+    pub fn decode(path: &[u8]) -> Result<Self, PathError> {
+        if path.len() < ADDR_SIZE + NEXT_OFFSET || !(path.len() - ADDR_SIZE).is_multiple_of(NEXT_OFFSET) {
+            return Err(PathError::InvalidLength(path.len()));
+        }
+        let mut tokens = vec![Address::from_slice(&path[..ADDR_SIZE])];
+        let mut fees = Vec::new();
+        for hop in path[ADDR_SIZE..].chunks(NEXT_OFFSET) {
+            fees.push(check_fee(u32::from_be_bytes([0, hop[0], hop[1], hop[2]]))?);
+            tokens.push(Address::from_slice(&hop[FEE_SIZE..]));
+        }
+        Ok(Self { tokens, fees })
+    }
+
+    /// Packed encoding, the inverse of [`V3Path::decode`]; fails on paths `decode` would reject.
+    pub fn encode(&self) -> Result<Bytes, PathError> {
+        if self.fees.is_empty() || self.tokens.len() != self.fees.len() + 1 {
+            return Err(PathError::InvalidHops { tokens: self.tokens.len(), fees: self.fees.len() });
+        }
+        let mut path = Vec::with_capacity(ADDR_SIZE + self.fees.len() * NEXT_OFFSET);
+        path.extend_from_slice(self.tokens[0].as_slice());
+        for (&fee, token) in self.fees.iter().zip(&self.tokens[1..]) {
+            path.extend_from_slice(&check_fee(fee)?.to_be_bytes()[4 - FEE_SIZE..]);
+            path.extend_from_slice(token.as_slice());
+        }
+        Ok(path.into())
+    }
+
+    /// The `factory` pools walked by the path, in path order.
+    pub fn hops(&self, factory: &PoolFactory) -> Vec<Hop> {
+        self.tokens.windows(2).zip(&self.fees).map(|(pair, &fee)| Hop::new(pair[0], pair[1], fee, factory)).collect()
+    }
+
+    /// The same hops walked the other way, e.g. an `exactOutput` path in swap order.
+    pub fn reversed(mut self) -> Self {
+        self.tokens.reverse();
+        self.fees.reverse();
+        self
+    }
+}
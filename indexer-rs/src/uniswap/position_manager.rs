@@ -0,0 +1,295 @@
+// Uniswap v3 NonfungiblePositionManager calldata.
+// Referencing to: https://github.com/Uniswap/v3-periphery/blob/main/contracts/interfaces/INonfungiblePositionManager.sol
+// and https://github.com/Uniswap/v3-periphery/blob/main/contracts/interfaces/IPeripheryPayments.sol
+//
+// Positions are ERC-721 tokens: `mint` opens one (its tokenId is only known from the receipt),
+// `increaseLiquidity` / `decreaseLiquidity` change its liquidity, `collect` withdraws the owed
+// tokens and `burn` destroys an emptied position. Interfaces batch these through `multicall`
+// with payment legs: `refundETH` after an ETH mint, and `collect` to the manager itself followed
+// by `unwrapWETH9` + `sweepToken` to pay out ETH. Every action found becomes one
+// `LiquidityAction`.
+
+use alloy::{
+    primitives::{address, Address, Bytes, B256, U256},
+    sol,
+    sol_types::SolInterface,
+};
+use thiserror::Error;
+
+use crate::{
+    nitro::tx::L2Transaction,
+    uniswap::path::{check_fee, PathError, PoolFactory},
+};
+
+/// NonfungiblePositionManager, at the same address on Ethereum and Arbitrum One.
+pub const POSITION_MANAGER: Address = address!("0xC36442b4a4522E871399CD717aBDD847Ab11FE88");
+/// Nested multicalls deeper than this are refused rather than followed.
+pub const MAX_MULTICALL_DEPTH: usize = 4;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    interface INonfungiblePositionManager {
+        struct MintParams {
+            address token0;
+            address token1;
+            uint24 fee;
+            int24 tickLower;
+            int24 tickUpper;
+            uint256 amount0Desired;
+            uint256 amount1Desired;
+            uint256 amount0Min;
+            uint256 amount1Min;
+            address recipient;
+            uint256 deadline;
+        }
+
+        struct IncreaseLiquidityParams {
+            uint256 tokenId;
+            uint256 amount0Desired;
+            uint256 amount1Desired;
+            uint256 amount0Min;
+            uint256 amount1Min;
+            uint256 deadline;
+        }
+
+        struct DecreaseLiquidityParams {
+            uint256 tokenId;
+            uint128 liquidity;
+            uint256 amount0Min;
+            uint256 amount1Min;
+            uint256 deadline;
+        }
+
+        struct CollectParams {
+            uint256 tokenId;
+            address recipient;
+            uint128 amount0Max;
+            uint128 amount1Max;
+        }
+
+        function mint(MintParams calldata params) external payable;
+        function increaseLiquidity(IncreaseLiquidityParams calldata params) external payable;
+        function decreaseLiquidity(DecreaseLiquidityParams calldata params) external payable;
+        function collect(CollectParams calldata params) external payable;
+        function burn(uint256 tokenId) external payable;
+        function multicall(bytes[] calldata data) external payable returns (bytes[] memory results);
+
+        function refundETH() external payable;
+        function unwrapWETH9(uint256 amountMinimum, address recipient) external payable;
+        function sweepToken(address token, uint256 amountMinimum, address recipient) external payable;
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PositionDecodeError {
+    #[error("calldata of {0} bytes has no function selector")]
+    NoSelector(usize),
+    #[error("selector {0} is not a NonfungiblePositionManager liquidity call or multicall")]
+    UnknownSelector(Bytes),
+    #[error("ABI decoding failed: {0}")]
+    Abi(#[from] alloy::sol_types::Error),
+    #[error("mint pool: {0}")]
+    Pool(#[from] PathError),
+    #[error("multicalls nested deeper than {MAX_MULTICALL_DEPTH} levels")]
+    MulticallTooDeep,
+}
+
+/// Which call an action was decoded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiquidityKind {
+    Mint,
+    IncreaseLiquidity,
+    DecreaseLiquidity,
+    Collect,
+    Burn,
+    RefundEth,
+    UnwrapWeth9,
+    SweepToken,
+}
+
+/// The v3 pool a position is minted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolKey {
+    pub token0: Address,
+    pub token1: Address,
+    /// Fee tier, in hundredths of a bip.
+    pub fee: u32,
+}
+
+impl PoolKey {
+    /// The `factory` pool of the pair and fee.
+    pub fn address(&self, factory: &PoolFactory) -> Address {
+        factory.pool_address(self.token0, self.token1, self.fee)
+    }
+}
+
+/// Token amounts of an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiquidityAmounts {
+    /// `mint` / `increaseLiquidity`: up to the desired amounts are added, at least the minimums.
+    Add { amount0_desired: U256, amount1_desired: U256, amount0_min: U256, amount1_min: U256 },
+    /// `decreaseLiquidity`: `liquidity` is removed for at least the minimums, owed until collected.
+    Remove { liquidity: u128, amount0_min: U256, amount1_min: U256 },
+    /// `collect`: up to the maximums of the owed tokens are withdrawn.
+    Collect { amount0_max: u128, amount1_max: u128 },
+    /// `unwrapWETH9` / `sweepToken`: the manager's whole balance of `token` (WETH9 for an unwrap),
+    /// at least `amount_minimum`.
+    Sweep { token: Option<Address>, amount_minimum: U256 },
+    /// `burn` / `refundETH`.
+    None,
+}
+
+/// A position change or payment leg, independent of how it was batched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiquidityAction {
+    pub kind: LiquidityKind,
+    /// Position acted on; `None` for `Mint` (assigned on execution) and the payment legs.
+    pub token_id: Option<U256>,
+    /// Pool of a `Mint`.
+    pub pool: Option<PoolKey>,
+    /// `(tickLower, tickUpper)` of a `Mint`.
+    pub tick_range: Option<(i32, i32)>,
+    pub amounts: LiquidityAmounts,
+    /// Receiver of the position (`Mint`) or tokens (`Collect` and the payment legs). A `Collect` to
+    /// the zero address leaves the tokens in the manager for a later unwrap or sweep.
+    pub recipient: Option<Address>,
+    pub deadline: Option<U256>,
+}
+
+/// A liquidity action together with the L2 transaction it was decoded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxLiquidityAction {
+    pub tx_hash: B256,
+    pub sender: Address,
+    pub action: LiquidityAction,
+}
+
+impl LiquidityAction {
+    fn new(kind: LiquidityKind, token_id: Option<U256>, amounts: LiquidityAmounts) -> Self {
+        Self { kind, token_id, pool: None, tick_range: None, amounts, recipient: None, deadline: None }
+    }
+}
+
+fn decode_action(
+    call: INonfungiblePositionManager::INonfungiblePositionManagerCalls,
+) -> Result<LiquidityAction, PositionDecodeError> {
+    use INonfungiblePositionManager::INonfungiblePositionManagerCalls as Call;
+    use LiquidityKind as Kind;
+    Ok(match call {
+        Call::mint(INonfungiblePositionManager::mintCall { params: p }) => {
+            let fee = check_fee(p.fee.to())?;
+            LiquidityAction {
+                pool: Some(PoolKey { token0: p.token0, token1: p.token1, fee }),
+                tick_range: Some((p.tickLower.as_i32(), p.tickUpper.as_i32())),
+                recipient: Some(p.recipient),
+                deadline: Some(p.deadline),
+                ..LiquidityAction::new(
+                    Kind::Mint,
+                    None,
+                    LiquidityAmounts::Add {
+                        amount0_desired: p.amount0Desired,
+                        amount1_desired: p.amount1Desired,
+                        amount0_min: p.amount0Min,
+                        amount1_min: p.amount1Min,
+                    },
+                )
+            }
+        }
+        Call::increaseLiquidity(INonfungiblePositionManager::increaseLiquidityCall { params: p }) => LiquidityAction {
+            deadline: Some(p.deadline),
+            ..LiquidityAction::new(
+                Kind::IncreaseLiquidity,
+                Some(p.tokenId),
+                LiquidityAmounts::Add {
+                    amount0_desired: p.amount0Desired,
+                    amount1_desired: p.amount1Desired,
+                    amount0_min: p.amount0Min,
+                    amount1_min: p.amount1Min,
+                },
+            )
+        },
+        Call::decreaseLiquidity(INonfungiblePositionManager::decreaseLiquidityCall { params: p }) => LiquidityAction {
+            deadline: Some(p.deadline),
+            ..LiquidityAction::new(
+                Kind::DecreaseLiquidity,
+                Some(p.tokenId),
+                LiquidityAmounts::Remove {
+                    liquidity: p.liquidity,
+                    amount0_min: p.amount0Min,
+                    amount1_min: p.amount1Min,
+                },
+            )
+        },
+        Call::collect(INonfungiblePositionManager::collectCall { params: p }) => LiquidityAction {
+            recipient: Some(p.recipient),
+            ..LiquidityAction::new(
+                Kind::Collect,
+                Some(p.tokenId),
+                LiquidityAmounts::Collect { amount0_max: p.amount0Max, amount1_max: p.amount1Max },
+            )
+        },
+        Call::burn(call) => LiquidityAction::new(Kind::Burn, Some(call.tokenId), LiquidityAmounts::None),
+        Call::refundETH(_) => LiquidityAction::new(Kind::RefundEth, None, LiquidityAmounts::None),
+        Call::unwrapWETH9(call) => LiquidityAction {
+            recipient: Some(call.recipient),
+            ..LiquidityAction::new(
+                Kind::UnwrapWeth9,
+                None,
+                LiquidityAmounts::Sweep { token: None, amount_minimum: call.amountMinimum },
+            )
+        },
+        Call::sweepToken(call) => LiquidityAction {
+            recipient: Some(call.recipient),
+            ..LiquidityAction::new(
+                Kind::SweepToken,
+                None,
+                LiquidityAmounts::Sweep { token: Some(call.token), amount_minimum: call.amountMinimum },
+            )
+        },
+        Call::multicall(_) => unreachable!("multicalls are expanded by decode_call"),
+    })
+}
+
+/// Actions of one call, or `None` if the selector is not one of the manager's liquidity, payment
+/// or multicall functions.
+fn decode_call(input: &[u8], depth: usize) -> Result<Option<Vec<LiquidityAction>>, PositionDecodeError> {
+    use INonfungiblePositionManager::INonfungiblePositionManagerCalls as Call;
+    let Some(selector) = input.get(..4).map(|s| <[u8; 4]>::try_from(s).expect("4 bytes")) else {
+        return Err(PositionDecodeError::NoSelector(input.len()));
+    };
+    if !Call::valid_selector(selector) {
+        return Ok(None);
+    }
+    let multicall = match Call::abi_decode(input)? {
+        Call::multicall(multicall) => multicall,
+        call => return Ok(Some(vec![decode_action(call)?])),
+    };
+    if depth >= MAX_MULTICALL_DEPTH {
+        return Err(PositionDecodeError::MulticallTooDeep);
+    }
+    let mut actions = Vec::new();
+    for call in multicall.data {
+        // Other calls (createAndInitializePoolIfNecessary, selfPermit, ...) change no position.
+        if let Some(found) = decode_call(&call, depth + 1)? {
+            actions.extend(found);
+        }
+    }
+    Ok(Some(actions))
+}
+
+/// Liquidity actions of a NonfungiblePositionManager call, in execution order.
+pub fn decode_position_manager_call(input: &[u8]) -> Result<Vec<LiquidityAction>, PositionDecodeError> {
+    decode_call(input, 0)?.ok_or_else(|| PositionDecodeError::UnknownSelector(Bytes::copy_from_slice(&input[..4])))
+}
+
+/// Liquidity actions of an L2 transaction sent to the position manager; empty for any other
+/// transaction.
+pub fn decode_liquidity_actions(tx: &L2Transaction) -> Result<Vec<TxLiquidityAction>, PositionDecodeError> {
+    if tx.to != Some(POSITION_MANAGER) {
+        return Ok(Vec::new());
+    }
+    Ok(decode_position_manager_call(&tx.input)?
+        .into_iter()
+        .map(|action| TxLiquidityAction { tx_hash: tx.hash, sender: tx.from, action })
+        .collect())
+}
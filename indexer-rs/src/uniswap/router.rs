@@ -0,0 +1,366 @@
+// Uniswap v3 SwapRouter and SwapRouter02 calldata.
+// Referencing to: https://github.com/Uniswap/v3-periphery/blob/main/contracts/interfaces/ISwapRouter.sol
+// and https://github.com/Uniswap/swap-router-contracts/blob/main/contracts/interfaces/IV3SwapRouter.sol
+//
+// SwapRouter takes a deadline in every swap's parameters; SwapRouter02 dropped it and checks the
+// deadline of `multicall(uint256 deadline, bytes[])` instead. Both routers batch calls through
+// `multicall`, with swaps next to helper calls (`unwrapWETH9`, `refundETH`, `selfPermit`, ...).
+// Every swap found, at any depth, becomes one `SwapIntent`.
+
+use alloy::{
+    primitives::{address, aliases::U160, Address, Bytes, U256},
+    sol,
+    sol_types::SolInterface,
+};
+use thiserror::Error;
+
+use crate::{
+    nitro::tx::L2Transaction,
+    uniswap::path::{check_fee, Hop, PathError, PoolFactory, V3Path},
+};
+
+/// SwapRouter, at the same address on Ethereum and Arbitrum One.
+pub const SWAP_ROUTER: Address = address!("0xE592427A0AEce92De3Edee1F18E0157C05861564");
+/// SwapRouter02, at the same address on Ethereum and Arbitrum One.
+pub const SWAP_ROUTER_02: Address = address!("0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45");
+/// SwapRouter02 and Universal Router recipient placeholder for the caller.
+pub const MSG_SENDER: Address = address!("0x0000000000000000000000000000000000000001");
+/// SwapRouter02 and Universal Router recipient placeholder for the router itself, which forwards the output later.
+pub const ADDRESS_THIS: Address = address!("0x0000000000000000000000000000000000000002");
+/// Nested multicalls deeper than this are refused rather than followed.
+pub const MAX_MULTICALL_DEPTH: usize = 4;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    interface ISwapRouter {
+        struct ExactInputSingleParams {
+            address tokenIn;
+            address tokenOut;
+            uint24 fee;
+            address recipient;
+            uint256 deadline;
+            uint256 amountIn;
+            uint256 amountOutMinimum;
+            uint160 sqrtPriceLimitX96;
+        }
+
+        struct ExactInputParams {
+            bytes path;
+            address recipient;
+            uint256 deadline;
+            uint256 amountIn;
+            uint256 amountOutMinimum;
+        }
+
+        struct ExactOutputSingleParams {
+            address tokenIn;
+            address tokenOut;
+            uint24 fee;
+            address recipient;
+            uint256 deadline;
+            uint256 amountOut;
+            uint256 amountInMaximum;
+            uint160 sqrtPriceLimitX96;
+        }
+
+        struct ExactOutputParams {
+            bytes path;
+            address recipient;
+            uint256 deadline;
+            uint256 amountOut;
+            uint256 amountInMaximum;
+        }
+
+        function exactInputSingle(ExactInputSingleParams calldata params) external payable returns (uint256 amountOut);
+        function exactInput(ExactInputParams calldata params) external payable returns (uint256 amountOut);
+        function exactOutputSingle(ExactOutputSingleParams calldata params) external payable returns (uint256 amountIn);
+        function exactOutput(ExactOutputParams calldata params) external payable returns (uint256 amountIn);
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    interface IV3SwapRouter {
+        struct ExactInputSingleParams {
+            address tokenIn;
+            address tokenOut;
+            uint24 fee;
+            address recipient;
+            uint256 amountIn;
+            uint256 amountOutMinimum;
+            uint160 sqrtPriceLimitX96;
+        }
+
+        struct ExactInputParams {
+            bytes path;
+            address recipient;
+            uint256 amountIn;
+            uint256 amountOutMinimum;
+        }
+
+        struct ExactOutputSingleParams {
+            address tokenIn;
+            address tokenOut;
+            uint24 fee;
+            address recipient;
+            uint256 amountOut;
+            uint256 amountInMaximum;
+            uint160 sqrtPriceLimitX96;
+        }
+
+        struct ExactOutputParams {
+            bytes path;
+            address recipient;
+            uint256 amountOut;
+            uint256 amountInMaximum;
+        }
+
+        function exactInputSingle(ExactInputSingleParams calldata params) external payable returns (uint256 amountOut);
+        function exactInput(ExactInputParams calldata params) external payable returns (uint256 amountOut);
+        function exactOutputSingle(ExactOutputSingleParams calldata params) external payable returns (uint256 amountIn);
+        function exactOutput(ExactOutputParams calldata params) external payable returns (uint256 amountIn);
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    interface IMulticall {
+        function multicall(bytes[] calldata data) external payable returns (bytes[] memory results);
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    interface IMulticallExtended {
+        function multicall(uint256 deadline, bytes[] calldata data) external payable returns (bytes[] memory results);
+        function multicall(bytes32 previousBlockhash, bytes[] calldata data) external payable returns (bytes[] memory results);
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RouterDecodeError {
+    #[error("calldata of {0} bytes has no function selector")]
+    NoSelector(usize),
+    #[error("selector {0} is not a SwapRouter swap or multicall")]
+    UnknownSelector(Bytes),
+    #[error("ABI decoding failed: {0}")]
+    Abi(#[from] alloy::sol_types::Error),
+    #[error("swap path: {0}")]
+    Path(#[from] PathError),
+    #[error("multicalls nested deeper than {MAX_MULTICALL_DEPTH} levels")]
+    MulticallTooDeep,
+}
+
+/// Which router interface a swap was encoded for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouterVersion {
+    SwapRouter,
+    SwapRouter02,
+    /// A V3 leg of a Universal Router command stream.
+    UniversalRouter,
+}
+
+/// What the swapper fixed and the bound on the other side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapAmounts {
+    /// Sell exactly `amount_in`; a SwapRouter02 `amount_in` of 0 and a Universal Router
+    /// `amount_in` of 2^255 spend the router's balance.
+    ExactIn { amount_in: U256, amount_out_minimum: U256 },
+    /// Buy exactly `amount_out`.
+    ExactOut { amount_out: U256, amount_in_maximum: U256 },
+}
+
+/// A swap requested from a v3 router, independent of how it was encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapIntent {
+    pub router: RouterVersion,
+    /// As encoded; may be [`MSG_SENDER`] or [`ADDRESS_THIS`] on SwapRouter02 and the Universal Router,
+    /// or the zero address (the router itself) on SwapRouter.
+    pub recipient: Address,
+    /// Tokens from the one paid in to the one received.
+    pub path: Vec<Address>,
+    /// Fee tier of each hop in swap order, in hundredths of a bip.
+    pub fees: Vec<u32>,
+    pub amounts: SwapAmounts,
+    /// Price limit of a single-pool swap; 0 means none.
+    pub sqrt_price_limit_x96: Option<U160>,
+    /// From the swap's parameters (SwapRouter), the enclosing `multicall` (SwapRouter02) or
+    /// `execute` (Universal Router).
+    pub deadline: Option<U256>,
+}
+
+impl SwapIntent {
+    /// The address receiving the output, with recipient placeholders resolved for a call from
+    /// `sender` to `router`.
+    pub fn resolved_recipient(&self, sender: Address, router: Address) -> Address {
+        match self.recipient {
+            // SwapRouter pays `address(0)` recipients to itself, e.g. before `unwrapWETH9`.
+            Address::ZERO if self.router == RouterVersion::SwapRouter => router,
+            MSG_SENDER if self.router != RouterVersion::SwapRouter => sender,
+            ADDRESS_THIS if self.router != RouterVersion::SwapRouter => router,
+            recipient => recipient,
+        }
+    }
+
+    /// The `factory` pools swapped through, in swap order.
+    pub fn hops(&self, factory: &PoolFactory) -> Vec<Hop> {
+        V3Path { tokens: self.path.clone(), fees: self.fees.clone() }.hops(factory)
+    }
+}
+
+fn single(
+    router: RouterVersion,
+    recipient: Address,
+    (token_in, token_out, fee): (Address, Address, u32),
+    amounts: SwapAmounts,
+    sqrt_price_limit_x96: U160,
+    deadline: Option<U256>,
+) -> SwapIntent {
+    SwapIntent {
+        router,
+        recipient,
+        path: vec![token_in, token_out],
+        fees: vec![fee],
+        amounts,
+        sqrt_price_limit_x96: Some(sqrt_price_limit_x96),
+        deadline,
+    }
+}
+
+pub(crate) fn multi_hop(
+    router: RouterVersion,
+    recipient: Address,
+    path: V3Path,
+    amounts: SwapAmounts,
+    deadline: Option<U256>,
+) -> SwapIntent {
+    SwapIntent { router, recipient, path: path.tokens, fees: path.fees, amounts, sqrt_price_limit_x96: None, deadline }
+}
+
+fn decode_swap_router(call: ISwapRouter::ISwapRouterCalls) -> Result<SwapIntent, RouterDecodeError> {
+    use ISwapRouter::ISwapRouterCalls as Call;
+    let router = RouterVersion::SwapRouter;
+    Ok(match call {
+        Call::exactInputSingle(ISwapRouter::exactInputSingleCall { params: p }) => single(
+            router,
+            p.recipient,
+            (p.tokenIn, p.tokenOut, check_fee(p.fee.to())?),
+            SwapAmounts::ExactIn { amount_in: p.amountIn, amount_out_minimum: p.amountOutMinimum },
+            p.sqrtPriceLimitX96,
+            Some(p.deadline),
+        ),
+        Call::exactInput(ISwapRouter::exactInputCall { params: p }) => multi_hop(
+            router,
+            p.recipient,
+            V3Path::decode(&p.path)?,
+            SwapAmounts::ExactIn { amount_in: p.amountIn, amount_out_minimum: p.amountOutMinimum },
+            Some(p.deadline),
+        ),
+        Call::exactOutputSingle(ISwapRouter::exactOutputSingleCall { params: p }) => single(
+            router,
+            p.recipient,
+            (p.tokenIn, p.tokenOut, check_fee(p.fee.to())?),
+            SwapAmounts::ExactOut { amount_out: p.amountOut, amount_in_maximum: p.amountInMaximum },
+            p.sqrtPriceLimitX96,
+            Some(p.deadline),
+        ),
+        Call::exactOutput(ISwapRouter::exactOutputCall { params: p }) => multi_hop(
+            router,
+            p.recipient,
+            V3Path::decode(&p.path)?.reversed(),
+            SwapAmounts::ExactOut { amount_out: p.amountOut, amount_in_maximum: p.amountInMaximum },
+            Some(p.deadline),
+        ),
+    })
+}
+
+fn decode_swap_router_02(
+    call: IV3SwapRouter::IV3SwapRouterCalls,
+    deadline: Option<U256>,
+) -> Result<SwapIntent, RouterDecodeError> {
+    use IV3SwapRouter::IV3SwapRouterCalls as Call;
+    let router = RouterVersion::SwapRouter02;
+    Ok(match call {
+        Call::exactInputSingle(IV3SwapRouter::exactInputSingleCall { params: p }) => single(
+            router,
+            p.recipient,
+            (p.tokenIn, p.tokenOut, check_fee(p.fee.to())?),
+            SwapAmounts::ExactIn { amount_in: p.amountIn, amount_out_minimum: p.amountOutMinimum },
+            p.sqrtPriceLimitX96,
+            deadline,
+        ),
+        Call::exactInput(IV3SwapRouter::exactInputCall { params: p }) => multi_hop(
+            router,
+            p.recipient,
+            V3Path::decode(&p.path)?,
+            SwapAmounts::ExactIn { amount_in: p.amountIn, amount_out_minimum: p.amountOutMinimum },
+            deadline,
+        ),
+        Call::exactOutputSingle(IV3SwapRouter::exactOutputSingleCall { params: p }) => single(
+            router,
+            p.recipient,
+            (p.tokenIn, p.tokenOut, check_fee(p.fee.to())?),
+            SwapAmounts::ExactOut { amount_out: p.amountOut, amount_in_maximum: p.amountInMaximum },
+            p.sqrtPriceLimitX96,
+            deadline,
+        ),
+        Call::exactOutput(IV3SwapRouter::exactOutputCall { params: p }) => multi_hop(
+            router,
+            p.recipient,
+            V3Path::decode(&p.path)?.reversed(),
+            SwapAmounts::ExactOut { amount_out: p.amountOut, amount_in_maximum: p.amountInMaximum },
+            deadline,
+        ),
+    })
+}
+
+/// Swaps of one call, or `None` if the selector is not a swap or multicall of either router.
+fn decode_call(
+    input: &[u8],
+    deadline: Option<U256>,
+    depth: usize,
+) -> Result<Option<Vec<SwapIntent>>, RouterDecodeError> {
+    let Some(selector) = input.get(..4).map(|s| <[u8; 4]>::try_from(s).expect("4 bytes")) else {
+        return Err(RouterDecodeError::NoSelector(input.len()));
+    };
+    let inner = |calls: Vec<Bytes>, deadline: Option<U256>| -> Result<Option<Vec<SwapIntent>>, RouterDecodeError> {
+        if depth >= MAX_MULTICALL_DEPTH {
+            return Err(RouterDecodeError::MulticallTooDeep);
+        }
+        let mut swaps = Vec::new();
+        for call in calls {
+            // Helper calls (unwrapWETH9, refundETH, selfPermit, ...) carry no swap.
+            if let Some(found) = decode_call(&call, deadline, depth + 1)? {
+                swaps.extend(found);
+            }
+        }
+        Ok(Some(swaps))
+    };
+
+    if ISwapRouter::ISwapRouterCalls::valid_selector(selector) {
+        let call = ISwapRouter::ISwapRouterCalls::abi_decode(input)?;
+        return Ok(Some(vec![decode_swap_router(call)?]));
+    }
+    if IV3SwapRouter::IV3SwapRouterCalls::valid_selector(selector) {
+        let call = IV3SwapRouter::IV3SwapRouterCalls::abi_decode(input)?;
+        return Ok(Some(vec![decode_swap_router_02(call, deadline)?]));
+    }
+    if IMulticall::IMulticallCalls::valid_selector(selector) {
+        let IMulticall::IMulticallCalls::multicall(call) = IMulticall::IMulticallCalls::abi_decode(input)?;
+        return inner(call.data, deadline);
+    }
+    if IMulticallExtended::IMulticallExtendedCalls::valid_selector(selector) {
+        return match IMulticallExtended::IMulticallExtendedCalls::abi_decode(input)? {
+            IMulticallExtended::IMulticallExtendedCalls::multicall_0(call) => inner(call.data, Some(call.deadline)),
+            IMulticallExtended::IMulticallExtendedCalls::multicall_1(call) => inner(call.data, deadline),
+        };
+    }
+    Ok(None)
+}
+
+/// Swap intents of a SwapRouter / SwapRouter02 call, in execution order.
+pub fn decode_router_call(input: &[u8]) -> Result<Vec<SwapIntent>, RouterDecodeError> {
+    decode_call(input, None, 0)?.ok_or_else(|| RouterDecodeError::UnknownSelector(Bytes::copy_from_slice(&input[..4])))
+}
+
+/// Swap intents of an L2 transaction sent to one of the routers; empty for any other transaction.
+pub fn decode_swaps(tx: &L2Transaction) -> Result<Vec<SwapIntent>, RouterDecodeError> {
+    match tx.to {
+        Some(to) if to == SWAP_ROUTER || to == SWAP_ROUTER_02 => decode_router_call(&tx.input),
+        _ => Ok(Vec::new()),
+    }
+}
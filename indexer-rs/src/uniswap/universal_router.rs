@@ -0,0 +1,323 @@
+// Uniswap Universal Router calldata.
+// Referencing to: https://github.com/Uniswap/universal-router/blob/main/contracts/libraries/Commands.sol
+// and https://github.com/Uniswap/universal-router/blob/main/contracts/base/Dispatcher.sol
+//
+// `execute(bytes commands, bytes[] inputs[, uint256 deadline])` runs a program: byte `i` of
+// `commands` names the command applied to `inputs[i]`, each input being the `abi.encode` of that
+// command's parameters. The low 6 bits of a command byte are its type; the high bit lets the
+// command revert without reverting the whole program. `EXECUTE_SUB_PLAN` runs a nested program
+// the same way. V3 swap commands become the same `SwapIntent`s as SwapRouter02 swaps, with
+// recipients using the same `MSG_SENDER` / `ADDRESS_THIS` placeholders.
+
+use alloy::{
+    primitives::{address, aliases::U160, Address, Bytes, U256},
+    sol,
+    sol_types::{SolInterface, SolValue},
+};
+use thiserror::Error;
+
+use crate::{
+    nitro::tx::L2Transaction,
+    uniswap::{
+        path::{PathError, V3Path},
+        router::{multi_hop, RouterVersion, SwapAmounts, SwapIntent},
+    },
+};
+
+/// Universal Router deployments on Arbitrum One: v1.2 and v1.
+pub const UNIVERSAL_ROUTERS: [Address; 2] =
+    [address!("0x5E325eDA8064b456f4781070C0738d849c824258"), address!("0x4C60051384bd2d3C01bfc845Cf5F4b44bcbE9de5")];
+/// Command byte flag: a failing command does not revert the program.
+pub const FLAG_ALLOW_REVERT: u8 = 0x80;
+/// Command byte mask of the command type.
+pub const COMMAND_TYPE_MASK: u8 = 0x3f;
+/// Nested sub-plans deeper than this are refused rather than followed.
+pub const MAX_SUB_PLAN_DEPTH: usize = 4;
+
+// Command types, see Commands.sol.
+const V3_SWAP_EXACT_IN: u8 = 0x00;
+const V3_SWAP_EXACT_OUT: u8 = 0x01;
+const PERMIT2_TRANSFER_FROM: u8 = 0x02;
+const PERMIT2_PERMIT_BATCH: u8 = 0x03;
+const SWEEP: u8 = 0x04;
+const TRANSFER: u8 = 0x05;
+const PAY_PORTION: u8 = 0x06;
+const V2_SWAP_EXACT_IN: u8 = 0x08;
+const V2_SWAP_EXACT_OUT: u8 = 0x09;
+const PERMIT2_PERMIT: u8 = 0x0a;
+const WRAP_ETH: u8 = 0x0b;
+const UNWRAP_WETH: u8 = 0x0c;
+const PERMIT2_TRANSFER_FROM_BATCH: u8 = 0x0d;
+const BALANCE_CHECK_ERC20: u8 = 0x0e;
+const EXECUTE_SUB_PLAN: u8 = 0x21;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    interface IUniversalRouter {
+        function execute(bytes calldata commands, bytes[] calldata inputs, uint256 deadline) external payable;
+        function execute(bytes calldata commands, bytes[] calldata inputs) external payable;
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    interface IAllowanceTransfer {
+        struct PermitDetails {
+            address token;
+            uint160 amount;
+            uint48 expiration;
+            uint48 nonce;
+        }
+
+        struct PermitSingle {
+            PermitDetails details;
+            address spender;
+            uint256 sigDeadline;
+        }
+
+        struct PermitBatch {
+            PermitDetails[] details;
+            address spender;
+            uint256 sigDeadline;
+        }
+
+        struct AllowanceTransferDetails {
+            address from;
+            address to;
+            uint160 amount;
+            address token;
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum UniversalRouterError {
+    #[error("calldata of {0} bytes has no function selector")]
+    NoSelector(usize),
+    #[error("selector {0} is not a Universal Router execute")]
+    UnknownSelector(Bytes),
+    #[error("ABI decoding failed: {0}")]
+    Abi(#[from] alloy::sol_types::Error),
+    #[error("swap path: {0}")]
+    Path(#[from] PathError),
+    #[error("{commands} commands for {inputs} inputs")]
+    LengthMismatch { commands: usize, inputs: usize },
+    #[error("sub-plans nested deeper than {MAX_SUB_PLAN_DEPTH} levels")]
+    SubPlanTooDeep,
+}
+
+/// A decoded command and its inputs. Recipients are as encoded and may be placeholders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `V3_SWAP_EXACT_IN` / `V3_SWAP_EXACT_OUT`, paid by the caller through Permit2 if
+    /// `payer_is_user`, else from the router's balance.
+    V3Swap {
+        swap: SwapIntent,
+        payer_is_user: bool,
+    },
+    /// `V2_SWAP_EXACT_IN` / `V2_SWAP_EXACT_OUT` through the pairs of `path`, in swap order.
+    V2Swap {
+        recipient: Address,
+        path: Vec<Address>,
+        amounts: SwapAmounts,
+        payer_is_user: bool,
+    },
+    /// Wrap at least `amount_min` of the router's ETH (all of it for 2^255) into WETH.
+    WrapEth {
+        recipient: Address,
+        amount_min: U256,
+    },
+    /// Unwrap the router's WETH, at least `amount_min`.
+    UnwrapWeth {
+        recipient: Address,
+        amount_min: U256,
+    },
+    /// Signed Permit2 allowance for the router (the signature is not kept).
+    Permit2Permit(IAllowanceTransfer::PermitSingle),
+    Permit2PermitBatch(IAllowanceTransfer::PermitBatch),
+    /// Pull `amount` of `token` from the caller through Permit2.
+    Permit2TransferFrom {
+        token: Address,
+        recipient: Address,
+        amount: U160,
+    },
+    Permit2TransferFromBatch(Vec<IAllowanceTransfer::AllowanceTransferDetails>),
+    /// Send the router's whole balance of `token` (ETH for the zero address), at least `amount_min`.
+    Sweep {
+        token: Address,
+        recipient: Address,
+        amount_min: U256,
+    },
+    /// Send `value` of `token` (ETH for the zero address) from the router.
+    Transfer {
+        token: Address,
+        recipient: Address,
+        value: U256,
+    },
+    /// Send `bips` / 10000 of the router's balance of `token`, typically an interface fee.
+    PayPortion {
+        token: Address,
+        recipient: Address,
+        bips: U256,
+    },
+    BalanceCheckErc20 {
+        owner: Address,
+        token: Address,
+        min_balance: U256,
+    },
+    /// `EXECUTE_SUB_PLAN`.
+    SubPlan(Vec<UniversalCommand>),
+    /// A command that is not decoded (NFT marketplaces, v4, ...), with its raw input.
+    Other {
+        command_type: u8,
+        input: Bytes,
+    },
+}
+
+/// One command of a Universal Router program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniversalCommand {
+    pub command: Command,
+    /// Whether the program continues if this command reverts.
+    pub allow_revert: bool,
+}
+
+impl UniversalCommand {
+    /// V3 swaps of this command, including those of sub-plans, in execution order.
+    pub fn v3_swaps(&self) -> Vec<&SwapIntent> {
+        match &self.command {
+            Command::V3Swap { swap, .. } => vec![swap],
+            Command::SubPlan(commands) => commands.iter().flat_map(UniversalCommand::v3_swaps).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn decode_command(
+    command_type: u8,
+    input: Bytes,
+    deadline: Option<U256>,
+    depth: usize,
+) -> Result<Command, UniversalRouterError> {
+    let router = RouterVersion::UniversalRouter;
+    Ok(match command_type {
+        V3_SWAP_EXACT_IN => {
+            let (recipient, amount_in, amount_out_minimum, path, payer_is_user) =
+                <(Address, U256, U256, Bytes, bool)>::abi_decode_params(&input)?;
+            let amounts = SwapAmounts::ExactIn { amount_in, amount_out_minimum };
+            let swap = multi_hop(router, recipient, V3Path::decode(&path)?, amounts, deadline);
+            Command::V3Swap { swap, payer_is_user }
+        }
+        V3_SWAP_EXACT_OUT => {
+            // As with SwapRouter's exactOutput, the path is encoded from the output token.
+            let (recipient, amount_out, amount_in_maximum, path, payer_is_user) =
+                <(Address, U256, U256, Bytes, bool)>::abi_decode_params(&input)?;
+            let amounts = SwapAmounts::ExactOut { amount_out, amount_in_maximum };
+            let swap = multi_hop(router, recipient, V3Path::decode(&path)?.reversed(), amounts, deadline);
+            Command::V3Swap { swap, payer_is_user }
+        }
+        V2_SWAP_EXACT_IN => {
+            let (recipient, amount_in, amount_out_minimum, path, payer_is_user) =
+                <(Address, U256, U256, Vec<Address>, bool)>::abi_decode_params(&input)?;
+            let amounts = SwapAmounts::ExactIn { amount_in, amount_out_minimum };
+            Command::V2Swap { recipient, path, amounts, payer_is_user }
+        }
+        V2_SWAP_EXACT_OUT => {
+            // Unlike V3, V2 paths are in swap order either way.
+            let (recipient, amount_out, amount_in_maximum, path, payer_is_user) =
+                <(Address, U256, U256, Vec<Address>, bool)>::abi_decode_params(&input)?;
+            let amounts = SwapAmounts::ExactOut { amount_out, amount_in_maximum };
+            Command::V2Swap { recipient, path, amounts, payer_is_user }
+        }
+        WRAP_ETH => {
+            let (recipient, amount_min) = <(Address, U256)>::abi_decode_params(&input)?;
+            Command::WrapEth { recipient, amount_min }
+        }
+        UNWRAP_WETH => {
+            let (recipient, amount_min) = <(Address, U256)>::abi_decode_params(&input)?;
+            Command::UnwrapWeth { recipient, amount_min }
+        }
+        PERMIT2_PERMIT => {
+            let (permit, _signature) = <(IAllowanceTransfer::PermitSingle, Bytes)>::abi_decode_params(&input)?;
+            Command::Permit2Permit(permit)
+        }
+        PERMIT2_PERMIT_BATCH => {
+            let (permit, _signature) = <(IAllowanceTransfer::PermitBatch, Bytes)>::abi_decode_params(&input)?;
+            Command::Permit2PermitBatch(permit)
+        }
+        PERMIT2_TRANSFER_FROM => {
+            let (token, recipient, amount) = <(Address, Address, U160)>::abi_decode_params(&input)?;
+            Command::Permit2TransferFrom { token, recipient, amount }
+        }
+        PERMIT2_TRANSFER_FROM_BATCH => {
+            let (details,) = <(Vec<IAllowanceTransfer::AllowanceTransferDetails>,)>::abi_decode_params(&input)?;
+            Command::Permit2TransferFromBatch(details)
+        }
+        SWEEP => {
+            let (token, recipient, amount_min) = <(Address, Address, U256)>::abi_decode_params(&input)?;
+            Command::Sweep { token, recipient, amount_min }
+        }
+        TRANSFER => {
+            let (token, recipient, value) = <(Address, Address, U256)>::abi_decode_params(&input)?;
+            Command::Transfer { token, recipient, value }
+        }
+        PAY_PORTION => {
+            let (token, recipient, bips) = <(Address, Address, U256)>::abi_decode_params(&input)?;
+            Command::PayPortion { token, recipient, bips }
+        }
+        BALANCE_CHECK_ERC20 => {
+            let (owner, token, min_balance) = <(Address, Address, U256)>::abi_decode_params(&input)?;
+            Command::BalanceCheckErc20 { owner, token, min_balance }
+        }
+        EXECUTE_SUB_PLAN => {
+            if depth >= MAX_SUB_PLAN_DEPTH {
+                return Err(UniversalRouterError::SubPlanTooDeep);
+            }
+            let (commands, inputs) = <(Bytes, Vec<Bytes>)>::abi_decode_params(&input)?;
+            Command::SubPlan(decode_program(&commands, inputs, deadline, depth + 1)?)
+        }
+        command_type => Command::Other { command_type, input },
+    })
+}
+
+fn decode_program(
+    commands: &[u8],
+    inputs: Vec<Bytes>,
+    deadline: Option<U256>,
+    depth: usize,
+) -> Result<Vec<UniversalCommand>, UniversalRouterError> {
+    if commands.len() != inputs.len() {
+        return Err(UniversalRouterError::LengthMismatch { commands: commands.len(), inputs: inputs.len() });
+    }
+    commands
+        .iter()
+        .zip(inputs)
+        .map(|(&command, input)| {
+            Ok(UniversalCommand {
+                command: decode_command(command & COMMAND_TYPE_MASK, input, deadline, depth)?,
+                allow_revert: command & FLAG_ALLOW_REVERT != 0,
+            })
+        })
+        .collect()
+}
+
+/// Commands of a Universal Router `execute` call, in execution order.
+pub fn decode_universal_router_call(input: &[u8]) -> Result<Vec<UniversalCommand>, UniversalRouterError> {
+    use IUniversalRouter::IUniversalRouterCalls as Call;
+    let Some(selector) = input.get(..4).map(|s| <[u8; 4]>::try_from(s).expect("4 bytes")) else {
+        return Err(UniversalRouterError::NoSelector(input.len()));
+    };
+    if !Call::valid_selector(selector) {
+        return Err(UniversalRouterError::UnknownSelector(Bytes::copy_from_slice(&selector)));
+    }
+    match Call::abi_decode(input)? {
+        Call::execute_0(call) => decode_program(&call.commands, call.inputs, Some(call.deadline), 0),
+        Call::execute_1(call) => decode_program(&call.commands, call.inputs, None, 0),
+    }
+}
+
+/// Commands of an L2 transaction sent to a Universal Router; empty for any other transaction.
+pub fn decode_universal_router_commands(tx: &L2Transaction) -> Result<Vec<UniversalCommand>, UniversalRouterError> {
+    match tx.to {
+        Some(to) if UNIVERSAL_ROUTERS.contains(&to) => decode_universal_router_call(&tx.input),
+        _ => Ok(Vec::new()),
+    }
+}
@@ -1,11 +1,28 @@
 pub const MAX_L2_MESSAGE_SIZE: u32 = 256 * 1024;
 pub const BROTLI_MESSAGE_HEADER_BYTE: u8 = 0;
 pub const DASMESSAGE_HEADER_FLAG: u8 = 0x80;
+pub const TREE_DAS_MESSAGE_HEADER_FLAG: u8 = 0x08;
+pub const L1_AUTHENTICATED_MESSAGE_HEADER_FLAG: u8 = 0x40;
+pub const ZEROHEAVY_MESSAGE_HEADER_FLAG: u8 = 0x20;
+pub const BLOB_HASHES_HEADER_FLAG: u8 = L1_AUTHENTICATED_MESSAGE_HEADER_FLAG | 0x10;
 pub const BATCH_SEGMENT_KIND_L2_MESSAGE: u8 = 0;
 pub const BATCH_SEGMENT_KIND_L2_MESSAGE_BROTLI: u8 = 1;
 pub const BATCH_SEGMENT_KIND_DELAYED_MESSAGES: u8 = 2;
+pub const BATCH_SEGMENT_KIND_ADVANCE_TIMESTAMP: u8 = 3;
+pub const BATCH_SEGMENT_KIND_ADVANCE_L1_BLOCK_NUMBER: u8 = 4;
+pub const L1_MESSAGE_TYPE_L2_MESSAGE: u8 = 3;
+pub const L1_MESSAGE_TYPE_END_OF_BLOCK: u8 = 6;
+pub const L1_MESSAGE_TYPE_L2_FUNDED_BY_L1: u8 = 7;
+pub const L1_MESSAGE_TYPE_ROLLUP_EVENT: u8 = 8;
 pub const L1_MESSAGE_TYPE_SUBMIT_RETRYABLE_TX: u8 = 9;
 pub const L1_MESSAGE_TYPE_ETH_DEPOSIT: u8 = 12;
+pub const L1_MESSAGE_TYPE_BATCH_POSTING_REPORT: u8 = 13;
+pub const L2_MESSAGE_KIND_UNSIGNED_USER_TX: u8 = 0;
+pub const L2_MESSAGE_KIND_CONTRACT_TX: u8 = 1;
+pub const L2_MESSAGE_KIND_NON_MUTATING_CALL: u8 = 2;
 pub const L2_MESSAGE_KIND_BATCH: u8 = 3;
 pub const L2_MESSAGE_KIND_SIGNED_TX: u8 = 4;
+pub const L2_MESSAGE_KIND_HEARTBEAT: u8 = 6;
+pub const L2_MESSAGE_KIND_SIGNED_COMPRESSED_TX: u8 = 7;
 pub const DELAYED_MSG_TO_BE_ADDED: u8 = 9;
+pub const ARBITRUM_ONE_CHAIN_ID: u64 = 42161;
@@ -0,0 +1,67 @@
+use alloy::primitives::{keccak256, Bytes, B256, U256};
+use indexer_rs::{
+    accumulator::{accumulate, verify_batch, AccumulatorError},
+    sequencer_inbox::{BatchDataLocation, BatchPayload, SequencerBatchDelivered, TimeBounds},
+};
+
+const BEFORE_ACC: B256 = B256::repeat_byte(0xb0);
+const DELAYED_ACC: B256 = B256::repeat_byte(0xde);
+
+fn event(data_hash: B256) -> SequencerBatchDelivered {
+    SequencerBatchDelivered {
+        batchSequenceNumber: U256::from(7),
+        beforeAcc: BEFORE_ACC,
+        afterAcc: accumulate(BEFORE_ACC, data_hash, DELAYED_ACC),
+        delayedAcc: DELAYED_ACC,
+        afterDelayedMessagesRead: U256::from(0x0102),
+        timeBounds: TimeBounds { minTimestamp: 1, maxTimestamp: 2, minBlockNumber: 3, maxBlockNumber: 4 },
+        dataLocation: BatchDataLocation::TxInput,
+    }
+}
+
+/// The 40-byte header the SequencerInbox packs for `event`.
+fn header() -> Vec<u8> {
+    [1u64, 2, 3, 4, 0x0102].iter().flat_map(|v| v.to_be_bytes()).collect()
+}
+
+#[test]
+fn recomputes_after_acc_for_every_data_location() {
+    assert_eq!(
+        accumulate(BEFORE_ACC, B256::repeat_byte(0x11), DELAYED_ACC),
+        keccak256([[0xb0; 32], [0x11; 32], [0xde; 32]].concat())
+    );
+
+    let data = Bytes::from_static(&[0x00, 0x1b, 0x2c]);
+    let calldata = event(keccak256([&header()[..], &data[..]].concat()));
+    assert_eq!(verify_batch(&calldata, &BatchPayload::Data(data), None), Ok(calldata.afterAcc));
+
+    let empty = event(keccak256(header()));
+    assert_eq!(verify_batch(&empty, &BatchPayload::NoData, None), Ok(empty.afterAcc));
+
+    let hashes = vec![B256::repeat_byte(0x01), B256::repeat_byte(0x02)];
+    let blobs = event(keccak256([&header()[..], &[0x50], &[0x01; 32], &[0x02; 32]].concat()));
+    assert_eq!(verify_batch(&blobs, &BatchPayload::Blobs(hashes.clone()), None), Ok(blobs.afterAcc));
+
+    // Blob hashes out of order commit to a different batch.
+    let swapped = BatchPayload::Blobs(hashes.into_iter().rev().collect());
+    assert!(matches!(verify_batch(&blobs, &swapped, None), Err(AccumulatorError::Mismatch { batch: 7, .. })));
+}
+
+#[test]
+fn refuses_data_and_chains_that_do_not_match() {
+    let data = Bytes::from_static(&[0x00, 0x1b, 0x2c]);
+    let committed = event(keccak256([&header()[..], &data[..]].concat()));
+
+    let tampered = BatchPayload::Data(Bytes::from_static(&[0x00, 0x1b, 0x2d]));
+    assert!(matches!(
+        verify_batch(&committed, &tampered, None),
+        Err(AccumulatorError::Mismatch { expected, .. }) if expected == committed.afterAcc
+    ));
+
+    let payload = BatchPayload::Data(data);
+    assert!(verify_batch(&committed, &payload, Some(BEFORE_ACC)).is_ok());
+    assert_eq!(
+        verify_batch(&committed, &payload, Some(B256::repeat_byte(0xee))),
+        Err(AccumulatorError::BrokenChain { batch: 7, expected: B256::repeat_byte(0xee), found: BEFORE_ACC })
+    );
+}
@@ -0,0 +1,43 @@
+use alloy::{
+    primitives::Address,
+    providers::{mock::Asserter, ProviderBuilder},
+    rpc::types::Log,
+};
+use indexer_rs::backfill::LogPager;
+
+#[tokio::test]
+async fn pages_logs_in_adaptive_chunks() {
+    let asserter = Asserter::new();
+    let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+    let mut pager = LogPager::batches(Address::repeat_byte(0x1c), 0, 9_999).with_chunk_size(4_000, 8_000);
+
+    // The node refuses 4000 blocks; the pager retries with 2000 and grows again afterwards.
+    asserter.push_failure_msg("query returned more than 10000 results");
+    asserter.push_success(&Vec::<Log>::new());
+    let (range, logs) = pager.next_page(&provider).await.unwrap().unwrap();
+    assert_eq!(range, 0..=1_999);
+    assert!(logs.is_empty());
+    assert_eq!(pager.chunk_size(), 4_000);
+
+    asserter.push_success(&Vec::<Log>::new());
+    assert_eq!(pager.next_page(&provider).await.unwrap().unwrap().0, 2_000..=5_999);
+    assert_eq!(pager.chunk_size(), 8_000);
+
+    // The last page is clamped to the end of the range.
+    asserter.push_success(&Vec::<Log>::new());
+    assert_eq!(pager.next_page(&provider).await.unwrap().unwrap().0, 6_000..=9_999);
+    assert!(pager.next_page(&provider).await.unwrap().is_none());
+    assert_eq!(pager.next_block(), 10_000);
+}
+
+#[tokio::test]
+async fn gives_up_once_the_chunk_is_a_single_block() {
+    let asserter = Asserter::new();
+    let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+    let mut pager = LogPager::batches(Address::repeat_byte(0x1c), 100, 200).with_chunk_size(2, 2);
+
+    asserter.push_failure_msg("block range too large");
+    asserter.push_failure_msg("block range too large");
+    assert!(pager.next_page(&provider).await.is_err());
+    assert_eq!(pager.next_block(), 100);
+}
@@ -0,0 +1,50 @@
+use alloy::{
+    providers::{mock::Asserter, ProviderBuilder},
+    rpc::types::Block,
+};
+use indexer_rs::beacon::{BeaconNetwork, SlotError};
+
+fn block(number: u64, timestamp: u64) -> Block {
+    let mut block: Block = Block::default();
+    block.header.inner.number = number;
+    block.header.inner.timestamp = timestamp;
+    block
+}
+
+#[test]
+fn maps_timestamps_to_slots_per_network() {
+    // (network, block timestamp, slot): the merge block and the first Dencun (epoch 269568, 132608
+    // and 29696) slots.
+    let known = [
+        (BeaconNetwork::Mainnet, 1_663_224_179, 4_700_013),
+        (BeaconNetwork::Mainnet, 1_710_338_135, 8_626_176),
+        (BeaconNetwork::Sepolia, 1_706_655_072, 4_243_456),
+        (BeaconNetwork::Holesky, 1_707_305_664, 950_272),
+    ];
+    for (network, timestamp, slot) in known {
+        assert_eq!(network.slot_at(timestamp).unwrap(), slot, "{network}");
+        assert_eq!(network.timestamp_at(slot), timestamp, "{network}");
+    }
+
+    assert!(matches!(BeaconNetwork::Mainnet.slot_at(1_710_338_136), Err(SlotError::Unaligned { .. })));
+    assert!(matches!(BeaconNetwork::Holesky.slot_at(1_663_224_179), Err(SlotError::BeforeGenesis { .. })));
+    assert_eq!("Sepolia".parse::<BeaconNetwork>().unwrap(), BeaconNetwork::Sepolia);
+    assert!(matches!("goerli".parse::<BeaconNetwork>(), Err(SlotError::UnknownNetwork(_))));
+}
+
+#[tokio::test]
+async fn resolves_the_slot_of_a_block() {
+    let asserter = Asserter::new();
+    let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+
+    // Block 19426587 (first Dencun block): 36970 slots were missed since the merge, so counting
+    // blocks from the merge (`block - 15537394 + 4700013`) would give slot 8589206.
+    asserter.push_success(&block(19_426_587, 1_710_338_135));
+    assert_eq!(BeaconNetwork::Mainnet.slot_of_block(&provider, 19_426_587).await.unwrap(), 8_626_176);
+
+    asserter.push_success(&Option::<Block>::None);
+    assert!(matches!(
+        BeaconNetwork::Mainnet.slot_of_block(&provider, 99_999_999).await,
+        Err(SlotError::BlockNotFound(99_999_999))
+    ));
+}
@@ -0,0 +1,142 @@
+use std::{fs::read_to_string, path::Path};
+
+use alloy::{
+    eips::eip4844::{kzg_to_versioned_hash, Bytes48},
+    primitives::B256,
+};
+use indexer_rs::nitro::batch_blobs::{check_versioned_hashes, decode_batch_blobs, BlobAssemblyError, FetchedBlob};
+use indexer_rs::nitro::blob::{
+    decode_blobs, encode_blobs, is_canonical_field_element, pack_blob, unpack_blob, BlobCodecError,
+    BYTES_PER_BLOB, BYTES_PER_FIELD_ELEMENT, USABLE_BYTES_PER_BLOB,
+};
+
+fn read_hex_fixture(name: &str) -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(name);
+    let text = read_to_string(&path).unwrap_or_else(|e| panic!("reading {}: {e}", path.display()));
+    hex::decode(text.trim()).expect("fixture is hex encoded")
+}
+
+fn assert_canonical(blob: &[u8]) {
+    assert_eq!(blob.len(), BYTES_PER_BLOB);
+    for (i, fe) in blob.chunks_exact(BYTES_PER_FIELD_ELEMENT).enumerate() {
+        assert!(is_canonical_field_element(fe), "field element {i} is not below the BLS modulus");
+    }
+}
+
+#[test]
+fn batch_payload_round_trips_through_one_blob() {
+    let payload = read_hex_fixture("batch_payload.bin");
+
+    let blobs = encode_blobs(&payload);
+    assert_eq!(blobs.len(), 1);
+    blobs.iter().for_each(|b| assert_canonical(b));
+
+    assert_eq!(decode_blobs(&blobs).unwrap(), payload);
+    // The blob is exactly what encoding the decoded payload gives back.
+    assert_eq!(encode_blobs(&decode_blobs(&blobs).unwrap()), blobs);
+    assert_eq!(blobs[0][1..5], [0xba, 0x01, 0xef, 0xd8]); // RLP header of 126,936 bytes
+}
+
+#[test]
+fn large_payload_spans_several_blobs() {
+    // Deterministic, incompressible-looking filler.
+    let mut state: u32 = 0x1234_5678;
+    let payload: Vec<u8> = (0..USABLE_BYTES_PER_BLOB * 2 + 12_345)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 24) as u8
+        })
+        .collect();
+
+    let blobs = encode_blobs(&payload);
+    assert_eq!(blobs.len(), 3);
+    blobs.iter().for_each(|b| assert_canonical(b));
+
+    assert_eq!(decode_blobs(&blobs).unwrap(), payload);
+    // A single blob of a multi-blob batch does not hold the whole RLP item.
+    assert!(matches!(decode_blobs(&blobs[..1]), Err(BlobCodecError::InvalidRlp(_))));
+}
+
+#[test]
+fn captured_blob_layout_is_lossless() {
+    let raw_blob = read_hex_fixture("raw_blob_1.txt");
+    assert_canonical(&raw_blob);
+
+    // The capture is an OP Stack blob (version 0, a 130,044-byte channel frame), not a Nitro
+    // batch: it does not carry `batch_payload.bin`, so only its field element layout is checked.
+    assert_eq!(raw_blob[1..5], [0x00, 0x01, 0xfb, 0xfc]);
+    assert_ne!(decode_blobs(&[&raw_blob]).unwrap(), read_hex_fixture("batch_payload.bin"));
+
+    let unpacked = unpack_blob(&raw_blob).unwrap();
+    assert_eq!(unpacked.len(), USABLE_BYTES_PER_BLOB);
+
+    let (repacked, rest) = pack_blob(&unpacked);
+    assert!(rest.is_empty());
+    assert_eq!(repacked, raw_blob);
+}
+
+#[test]
+fn rejects_malformed_blobs() {
+    assert_eq!(
+        decode_blobs(&[vec![0u8; BYTES_PER_BLOB - 1]]),
+        Err(BlobCodecError::InvalidBlobLength { blob: 0, len: BYTES_PER_BLOB - 1 })
+    );
+
+    let mut blob = encode_blobs(b"nitro").remove(0);
+    blob[3 * BYTES_PER_FIELD_ELEMENT] = 0x40;
+    assert!(!is_canonical_field_element(&[0x74; BYTES_PER_FIELD_ELEMENT]));
+    assert_eq!(
+        decode_blobs(&[blob]),
+        Err(BlobCodecError::NonCanonicalFieldElement { blob: 0, index: 3, byte: 0x40 })
+    );
+}
+
+#[test]
+fn multi_blob_batch_is_reassembled_in_versioned_hash_order() {
+    let payload: Vec<u8> = (0..USABLE_BYTES_PER_BLOB + 100).map(|i| (i % 251) as u8).collect();
+    let blobs = encode_blobs(&payload);
+    let hashes = [B256::repeat_byte(0xaa), B256::repeat_byte(0xbb)];
+    let fetched = |index: [Option<usize>; 2]| {
+        vec![
+            // Source returns the blobs reversed, plus a blob of another rollup.
+            FetchedBlob { versioned_hash: hashes[1], index: index[1], data: blobs[1].clone(), proof: None },
+            FetchedBlob { versioned_hash: B256::repeat_byte(0xcc), index: None, data: vec![], proof: None },
+            FetchedBlob { versioned_hash: hashes[0], index: index[0], data: blobs[0].clone(), proof: None },
+        ]
+    };
+
+    assert_eq!(decode_batch_blobs(&hashes, fetched([None, None])).unwrap(), payload);
+    assert_eq!(decode_batch_blobs(&hashes, fetched([Some(0), Some(1)])).unwrap(), payload);
+    assert_eq!(
+        decode_batch_blobs(&hashes, fetched([Some(1), Some(0)])),
+        Err(BlobAssemblyError::OutOfOrder { versioned_hash: hashes[1], expected: 1, reported: 0 })
+    );
+
+    let mut missing = fetched([None, None]);
+    missing.remove(0);
+    assert_eq!(
+        decode_batch_blobs(&hashes, missing),
+        Err(BlobAssemblyError::MissingBlob { index: 1, versioned_hash: hashes[1] })
+    );
+}
+
+#[test]
+fn blob_commitments_must_match_versioned_hashes_in_order() {
+    // The commitment to the all-zero blob is the point at infinity.
+    let mut zero = [0u8; 48];
+    zero[0] = 0xc0;
+    let zero = Bytes48::from(zero);
+    let zero_hash: B256 = "0x010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014".parse().unwrap();
+    let other = Bytes48::repeat_byte(0x11);
+    let other_hash = kzg_to_versioned_hash(other.as_slice());
+
+    assert_eq!(check_versioned_hashes(&[zero_hash, other_hash], &[zero, other]), Ok(()));
+    assert_eq!(
+        check_versioned_hashes(&[zero_hash, other_hash], &[other, zero]),
+        Err(BlobAssemblyError::VersionedHashMismatch { index: 0, expected: zero_hash, computed: other_hash })
+    );
+    assert_eq!(
+        check_versioned_hashes(&[zero_hash, other_hash], &[zero]),
+        Err(BlobAssemblyError::CommitmentCount { expected: 2, found: 1 })
+    );
+}
@@ -0,0 +1,204 @@
+use alloy::{eips::eip4844::kzg_to_versioned_hash, hex, primitives::B256};
+use async_trait::async_trait;
+use axum::{extract::Path, http::StatusCode, routing::get, Json, Router};
+use indexer_rs::nitro::{
+    batch_blobs::FetchedBlob,
+    blob::encode_blobs,
+    blob_source::{
+        BeaconBlobSource, BlobSource, BlobSourceError, BlobscanBlobSource, DirectoryBlobSource, FallbackBlobSource,
+        UnavailableBatch, UnavailableBatchLog,
+    },
+    kzg::KzgVerifier,
+};
+use serde_json::{json, Value};
+
+/// Blobs of `payload` with their versioned hashes and the sidecar JSON a beacon node serves for them.
+fn blobs(payload: &[u8]) -> Vec<(B256, Vec<u8>, Value)> {
+    let blobs = encode_blobs(payload);
+    let commitments = KzgVerifier::embedded().commitments(&blobs).unwrap();
+    blobs
+        .into_iter()
+        .zip(commitments)
+        .enumerate()
+        .map(|(index, (blob, commitment))| {
+            let sidecar = json!({
+                "index": index.to_string(),
+                "blob": hex::encode_prefixed(&blob),
+                "kzg_commitment": commitment.to_string(),
+                "kzg_proof": format!("0x{}", "00".repeat(48)),
+            });
+            (kzg_to_versioned_hash(commitment.as_slice()), blob, sidecar)
+        })
+        .collect()
+}
+
+/// A source that has one blob, has nothing, or is down.
+enum Stub {
+    Has(FetchedBlob),
+    Missing,
+    Down,
+}
+
+#[async_trait]
+impl BlobSource for Stub {
+    fn name(&self) -> &str {
+        "stub"
+    }
+
+    async fn fetch_blob(&self, slot: u64, versioned_hash: B256) -> Result<FetchedBlob, BlobSourceError> {
+        match self {
+            Stub::Has(blob) if blob.versioned_hash == versioned_hash => Ok(blob.clone()),
+            Stub::Down => Err(BlobSourceError::InvalidResponse("503 Service Unavailable".to_string())),
+            _ => Err(BlobSourceError::NotFound { slot, versioned_hash }),
+        }
+    }
+}
+
+/// Serve the router built for the server's base URL on a local port and return that URL.
+async fn serve(app: impl FnOnce(&str) -> Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let app = app(&url);
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+#[tokio::test]
+async fn replays_blobs_from_a_directory() {
+    let dir = std::env::temp_dir().join(format!("indexer-rs-blobs-{}", std::process::id()));
+    let source = DirectoryBlobSource::new(&dir);
+    let [(hash, blob, _), (hex_hash, hex_blob, _)] = <[_; 2]>::try_from(blobs(&[7u8; 200_000])).unwrap();
+
+    source.store(&FetchedBlob { versioned_hash: hash, index: None, data: blob.clone(), proof: None }).await.unwrap();
+    // Captures saved as hex text are accepted too.
+    std::fs::write(source.path_for(hex_hash), hex::encode_prefixed(&hex_blob)).unwrap();
+
+    let fetched = source.fetch_blobs(8_000_000, &[hash, hex_hash]).await.unwrap();
+    assert_eq!(
+        fetched.iter().map(|b| (b.versioned_hash, &b.data)).collect::<Vec<_>>(),
+        [(hash, &blob), (hex_hash, &hex_blob)]
+    );
+
+    let missing = B256::repeat_byte(0x01);
+    assert!(matches!(
+        source.fetch_blob(8_000_000, missing).await,
+        Err(BlobSourceError::NotFound { slot: 8_000_000, versioned_hash }) if versioned_hash == missing
+    ));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn fetches_blob_sidecars_from_a_beacon_node() {
+    let blobs = blobs(&[3u8; 200_000]);
+    let sidecars: Vec<Value> = blobs.iter().map(|(_, _, sidecar)| sidecar.clone()).collect();
+    let app = |_: &str| {
+        Router::new().route(
+            "/eth/v1/beacon/blob_sidecars/{slot}",
+            get(move |Path(slot): Path<u64>| {
+                let sidecars = sidecars.clone();
+                async move {
+                    match slot {
+                        8_000_000 => Ok(Json(json!({ "data": sidecars }))),
+                        _ => Err(StatusCode::NOT_FOUND),
+                    }
+                }
+            }),
+        )
+    };
+    let source = BeaconBlobSource::new(format!("{}/", serve(app).await));
+
+    // Returned in the order asked for, whatever the order of the sidecars in the block.
+    let fetched = source.fetch_blobs(8_000_000, &[blobs[1].0, blobs[0].0]).await.unwrap();
+    assert_eq!(fetched.iter().map(|b| b.versioned_hash).collect::<Vec<_>>(), [blobs[1].0, blobs[0].0]);
+    assert_eq!(fetched[1].data, blobs[0].1);
+    assert!(fetched.iter().all(|b| b.index.is_none() && b.proof.is_some()));
+
+    assert!(matches!(
+        source.fetch_blobs(8_000_000, &[blobs[0].0, B256::repeat_byte(0x01)]).await,
+        Err(BlobSourceError::NotFound { .. })
+    ));
+    assert!(matches!(source.fetch_blob(8_000_001, blobs[0].0).await, Err(BlobSourceError::NotFound { .. })));
+}
+
+#[tokio::test]
+async fn follows_blobscan_storage_references() {
+    let [(hash, blob, sidecar)] = <[_; 1]>::try_from(blobs(b"batch")).unwrap();
+    let stored = hex::encode_prefixed(&blob);
+    let known = hash.to_string();
+    let app = |base: &str| {
+        let base = base.to_string();
+        Router::new()
+            .route(
+                "/blobs/{hash}",
+                get(move |Path(requested): Path<String>| async move {
+                    if requested != known {
+                        return Err(StatusCode::NOT_FOUND);
+                    }
+                    // The first storage is down; the second serves the blob as hex text.
+                    let storage = |path: &str| json!({ "storage": "test", "url": format!("{base}{path}") });
+                    Ok(Json(json!({
+                        "versionedHash": known,
+                        "proof": sidecar["kzg_proof"],
+                        "dataStorageReferences": [storage("/missing"), storage("/blob.txt")],
+                    })))
+                }),
+            )
+            .route("/blob.txt", get(move || async move { stored }))
+    };
+    let source = BlobscanBlobSource::new(serve(app).await);
+
+    let fetched = source.fetch_blob(8_000_000, hash).await.unwrap();
+    assert_eq!(fetched.data, blob);
+    assert!(fetched.proof.is_some());
+    assert!(matches!(
+        source.fetch_blob(8_000_000, B256::repeat_byte(0x01)).await,
+        Err(BlobSourceError::NotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn falls_back_through_archival_sources() {
+    let [(hash, data, _)] = <[_; 1]>::try_from(blobs(b"old batch")).unwrap();
+    let blob = FetchedBlob { versioned_hash: hash, index: None, data, proof: None };
+    let source = |stubs: Vec<Stub>| {
+        FallbackBlobSource::new(
+            stubs.into_iter().map(|s| Box::new(s) as Box<dyn BlobSource>).collect(),
+            KzgVerifier::embedded(),
+        )
+    };
+
+    // Pruned by the beacon node, served by the second archive.
+    let fetched = source(vec![Stub::Missing, Stub::Down, Stub::Has(blob.clone())]).fetch_blobs(9, &[hash]).await;
+    assert_eq!(fetched.unwrap()[0].data, blob.data);
+
+    // Missing everywhere is permanent; a source that is down means the batch can still be retried.
+    assert!(matches!(
+        source(vec![Stub::Missing, Stub::Missing]).fetch_blobs(9, &[hash]).await,
+        Err(BlobSourceError::Unavailable { slot: 9, sources: 2 })
+    ));
+    assert!(matches!(
+        source(vec![Stub::Missing, Stub::Down]).fetch_blobs(9, &[hash]).await,
+        Err(BlobSourceError::InvalidResponse(_))
+    ));
+
+    // A source serving other data under the versioned hash is passed over; if none serves the
+    // real blob, the batch is retried rather than rejected.
+    let forged = || Stub::Has(FetchedBlob { data: encode_blobs(b"forged batch").remove(0), ..blob.clone() });
+    let fetched = source(vec![forged(), Stub::Has(blob.clone())]).fetch_blobs(9, &[hash]).await.unwrap();
+    assert_eq!(fetched[0].data, blob.data);
+    assert!(matches!(
+        source(vec![forged(), Stub::Missing]).fetch_blobs(9, &[hash]).await,
+        Err(BlobSourceError::Inauthentic { slot: 9, sources: 1 })
+    ));
+
+    let path = std::env::temp_dir().join(format!("indexer-rs-unavailable-{}.jsonl", std::process::id()));
+    let log = UnavailableBatchLog::new(&path);
+    assert_eq!(log.load().unwrap(), []);
+    let skipped =
+        UnavailableBatch { batch_sequence_number: 7, l1_block: 19_000_000, slot: 9, versioned_hashes: vec![hash] };
+    log.record(&skipped).unwrap();
+    log.record(&UnavailableBatch { batch_sequence_number: 8, ..skipped.clone() }).unwrap();
+    let recorded: Vec<u64> = log.load().unwrap().iter().map(|b| b.batch_sequence_number).collect();
+    assert_eq!(recorded, [7, 8]);
+    std::fs::remove_file(path).unwrap();
+}
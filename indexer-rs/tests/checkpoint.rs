@@ -0,0 +1,122 @@
+use alloy::{
+    primitives::{Address, B256, U256},
+    providers::{mock::Asserter, ProviderBuilder},
+    rpc::types::Log,
+    sol_types::SolEvent,
+};
+use indexer_rs::{
+    accumulator::{accumulate, batch_data_hash, verify_batch, AccumulatorError},
+    checkpoint::{
+        fetch_missing_batches, BatchOrder, Checkpoint, CheckpointError, CheckpointStore, RejectedBatch,
+        RejectedBatchLog,
+    },
+    sequencer_inbox::{batch_header, BatchDataLocation, BatchPayload, SequencerBatchDelivered, TimeBounds},
+};
+
+const CURSOR: Checkpoint = Checkpoint {
+    batch_sequence_number: 10,
+    l1_block: 19_000_000,
+    log_index: 3,
+    after_delayed_messages: 42,
+    after_acc: None,
+};
+
+fn batch_log(seq: u64, block: u64) -> Log {
+    let event = SequencerBatchDelivered {
+        batchSequenceNumber: U256::from(seq),
+        beforeAcc: B256::ZERO,
+        afterAcc: B256::ZERO,
+        delayedAcc: B256::ZERO,
+        afterDelayedMessagesRead: U256::from(42),
+        timeBounds: TimeBounds { minTimestamp: 0, maxTimestamp: 0, minBlockNumber: 0, maxBlockNumber: 0 },
+        dataLocation: BatchDataLocation::NoData,
+    };
+    Log {
+        inner: alloy::primitives::Log { address: Address::repeat_byte(0x1c), data: event.encode_log_data() },
+        block_number: Some(block),
+        log_index: Some(0),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn orders_batches_against_the_cursor() {
+    assert_eq!(BatchOrder::of(None, 7), BatchOrder::Next);
+    assert_eq!(BatchOrder::of(Some(&CURSOR), 11), BatchOrder::Next);
+    assert_eq!(BatchOrder::of(Some(&CURSOR), 10), BatchOrder::Duplicate);
+    assert_eq!(BatchOrder::of(Some(&CURSOR), 3), BatchOrder::Duplicate);
+    assert_eq!(BatchOrder::of(Some(&CURSOR), 14), BatchOrder::Gap(11..=13));
+}
+
+#[test]
+fn persists_the_cursor() {
+    let path = std::env::temp_dir().join(format!("indexer-rs-checkpoint-{}.json", std::process::id()));
+    let store = CheckpointStore::new(&path);
+    assert_eq!(store.load().unwrap(), None);
+
+    store.save(&CURSOR).unwrap();
+    let next = Checkpoint { batch_sequence_number: 11, log_index: 0, ..CURSOR };
+    store.save(&next).unwrap();
+    assert_eq!(CheckpointStore::new(&path).load().unwrap(), Some(next));
+
+    // A reorg orphaning every batch of a first run clears the cursor.
+    store.clear().unwrap();
+    assert_eq!(store.load().unwrap(), None);
+    store.clear().unwrap();
+
+    std::fs::write(&path, b"{\"batch_sequence_number\":").unwrap();
+    assert!(matches!(store.load(), Err(CheckpointError::Corrupt(_))));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn refetches_the_batches_of_a_gap() {
+    let asserter = Asserter::new();
+    let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+    let inbox = Address::repeat_byte(0x1c);
+
+    // The cursor's own batch and the one that revealed the gap are in range but not missing.
+    asserter.push_success(&vec![
+        batch_log(10, 19_000_000),
+        batch_log(12, 19_000_400),
+        batch_log(11, 19_000_100),
+        batch_log(13, 19_000_500),
+    ]);
+    let found = fetch_missing_batches(&provider, inbox, 11..=12, 19_000_000, 19_000_500).await.unwrap();
+    let found: Vec<(u64, Option<u64>)> = found.iter().map(|(seq, log)| (*seq, log.block_number)).collect();
+    assert_eq!(found, vec![(11, Some(19_000_100)), (12, Some(19_000_400))]);
+
+    asserter.push_success(&vec![batch_log(11, 19_000_100), batch_log(13, 19_000_500)]);
+    assert!(matches!(
+        fetch_missing_batches(&provider, inbox, 11..=12, 19_000_000, 19_000_500).await,
+        Err(CheckpointError::BatchNotFound(12))
+    ));
+}
+
+#[test]
+fn moves_the_cursor_past_rejected_batches() {
+    // Batch 11 commits to data other than what was fetched.
+    let mut event = SequencerBatchDelivered::decode_log(&batch_log(11, 19_000_100).inner).unwrap().data;
+    event.beforeAcc = B256::repeat_byte(0xaa);
+    event.afterAcc = B256::repeat_byte(0xbb);
+    let err = verify_batch(&event, &BatchPayload::NoData, None).unwrap_err();
+    assert!(matches!(err, AccumulatorError::Mismatch { batch: 11, .. }));
+
+    let path = std::env::temp_dir().join(format!("indexer-rs-rejected-{}.jsonl", std::process::id()));
+    let log = RejectedBatchLog::new(&path);
+    assert_eq!(log.load().unwrap(), []);
+    let rejected = RejectedBatch::new(&event, 19_000_100, 4, &err);
+    log.record(&rejected).unwrap();
+    assert_eq!(log.load().unwrap(), vec![rejected.clone()]);
+    std::fs::remove_file(path).unwrap();
+
+    // The batch is not refetched as a gap, and the next one chains from the recorded afterAcc.
+    let cursor = rejected.checkpoint();
+    assert_eq!((cursor.batch_sequence_number, cursor.log_index, cursor.after_delayed_messages), (11, 4, 42));
+    assert_eq!(BatchOrder::of(Some(&cursor), 11), BatchOrder::Duplicate);
+    assert_eq!(BatchOrder::of(Some(&cursor), 12), BatchOrder::Next);
+    let mut next = SequencerBatchDelivered { batchSequenceNumber: U256::from(12), beforeAcc: event.afterAcc, ..event };
+    next.afterAcc =
+        accumulate(next.beforeAcc, batch_data_hash(&batch_header(&next), &BatchPayload::NoData), next.delayedAcc);
+    assert_eq!(verify_batch(&next, &BatchPayload::NoData, cursor.after_acc), Ok(next.afterAcc));
+}
@@ -0,0 +1,137 @@
+use std::io::Write;
+
+use alloy::primitives::{b256, keccak256, B256};
+use indexer_rs::nitro::{
+    batch::{BatchHeader, L2Message, Segment},
+    das::{
+        das_tree_hash, decode_das_batch, flat_hash_to_tree_hash, DasCertificate, DasError, DirectoryDasReader,
+        DAS_TREE_BIN_SIZE, MIN_DAS_CERT_LIFETIME_SECS,
+    },
+};
+
+const HEADER: BatchHeader = BatchHeader {
+    min_timestamp: 1_700_000_000,
+    max_timestamp: 1_700_000_600,
+    min_l1_block: 18_500_000,
+    max_l1_block: 18_500_050,
+    after_delayed_messages: 42,
+};
+
+fn certificate(flag: u8, data_hash: B256, timeout: u64, version: Option<u8>) -> Vec<u8> {
+    let mut out = vec![flag];
+    out.extend_from_slice(&[0x11; 32]);
+    out.extend_from_slice(data_hash.as_slice());
+    out.extend_from_slice(&timeout.to_be_bytes());
+    out.extend(version);
+    out.extend_from_slice(&0b1011u64.to_be_bytes());
+    out.extend_from_slice(&[0xaa; 96]);
+    out
+}
+
+/// A brotli payload holding a single heartbeat L2 message.
+fn sequencer_payload() -> Vec<u8> {
+    let mut compressed = Vec::new();
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
+        writer.write_all(&alloy::rlp::encode(&[0u8, 6][..])).unwrap();
+    }
+    let mut payload = vec![0x00];
+    payload.extend(compressed);
+    payload
+}
+
+fn das_directory(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("indexer-rs-das-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn decodes_certificates_with_and_without_version() {
+    let hash = B256::repeat_byte(0x22);
+    let cert = DasCertificate::decode(&certificate(0x80, hash, 1_800_000_000, None)).unwrap();
+    assert_eq!(cert.header_byte, 0x80);
+    assert_eq!(cert.keyset_hash, B256::repeat_byte(0x11));
+    assert_eq!(cert.data_hash, hash);
+    assert_eq!(cert.timeout, 1_800_000_000);
+    assert_eq!(cert.version, 0);
+    assert_eq!(cert.signers_mask, 0b1011);
+    assert_eq!(cert.signer_count(), 3);
+    assert_eq!(cert.signature.as_slice(), &[0xaa; 96]);
+
+    let tree = DasCertificate::decode(&certificate(0x88, hash, 1_800_000_000, Some(1))).unwrap();
+    assert_eq!((tree.version, tree.signers_mask), (1, 0b1011));
+
+    let mut truncated = certificate(0x88, hash, 1_800_000_000, Some(1));
+    truncated.truncate(100);
+    assert!(matches!(
+        DasCertificate::decode(&truncated),
+        Err(DasError::Truncated { field: "signature", needed: 96, available: 18 })
+    ));
+    assert!(matches!(DasCertificate::decode(&[0x00; 200]), Err(DasError::NotDas(0x00))));
+}
+
+#[test]
+fn computes_das_tree_roots() {
+    let leaf = |bin: &[u8]| keccak256([&[0xfe][..], keccak256(bin).as_slice()].concat());
+    let node = |l: B256, r: B256, size: u32| keccak256([&[0xff][..], l.as_slice(), r.as_slice(), &size.to_be_bytes()].concat());
+
+    // A single bin is its own root, which is how version 0 flat hashes map to tree hashes.
+    let small = b"nitro".to_vec();
+    assert_eq!(das_tree_hash(&small), leaf(&small));
+    assert_eq!(das_tree_hash(&small), flat_hash_to_tree_hash(keccak256(&small)));
+    // An empty preimage is a single empty leaf, keccak256(0xfe || keccak256("")) as in Nitro.
+    assert_eq!(das_tree_hash(&[]), leaf(&[]));
+    assert_eq!(das_tree_hash(&[]), b256!("0xcd2717b64170dc19cfef3dedd2076c9f7457d4f534a998dd8236ffeb40214bf5"));
+
+    // Three bins: the odd one bubbles up to pair with the first node.
+    let data = vec![7u8; 2 * DAS_TREE_BIN_SIZE + 10];
+    let bins: Vec<&[u8]> = data.chunks(DAS_TREE_BIN_SIZE).collect();
+    let first = node(leaf(bins[0]), leaf(bins[1]), 2 * DAS_TREE_BIN_SIZE as u32);
+    assert_eq!(das_tree_hash(&data), node(first, leaf(bins[2]), data.len() as u32));
+}
+
+#[tokio::test]
+async fn resolves_das_batches_from_a_directory() {
+    let dir = das_directory("resolve");
+    let reader = DirectoryDasReader::new(&dir);
+    let payload = sequencer_payload();
+    let timeout = HEADER.max_timestamp + MIN_DAS_CERT_LIFETIME_SECS;
+
+    // Version 1: stored and certified under the tree hash.
+    let tree_hash = das_tree_hash(&payload);
+    std::fs::write(reader.path_for(tree_hash), &payload).unwrap();
+    let (cert, batch) = decode_das_batch(&reader, HEADER, &certificate(0x88, tree_hash, timeout, Some(1)))
+        .await
+        .unwrap();
+    assert_eq!(cert.data_hash, tree_hash);
+    assert_eq!(batch.segments, vec![Segment::L2Message { compressed: false, message: L2Message::Heartbeat }]);
+
+    // Version 0: certified with the flat keccak hash, served under the tree hash.
+    let flat_hash = keccak256(&payload);
+    let (_, batch) = decode_das_batch(&reader, HEADER, &certificate(0x80, flat_hash, timeout, None)).await.unwrap();
+    assert_eq!(batch.segments.len(), 1);
+
+    // Data that does not match the certified hash is refused.
+    let forged = B256::repeat_byte(0x33);
+    std::fs::write(reader.path_for(forged), &payload).unwrap();
+    assert!(matches!(
+        decode_das_batch(&reader, HEADER, &certificate(0x88, forged, timeout, Some(1))).await,
+        Err(DasError::HashMismatch(hash)) if hash == forged
+    ));
+
+    assert!(matches!(
+        decode_das_batch(&reader, HEADER, &certificate(0x88, B256::repeat_byte(0x44), timeout, Some(1))).await,
+        Err(DasError::NotFound(_))
+    ));
+    assert!(matches!(
+        decode_das_batch(&reader, HEADER, &certificate(0x88, tree_hash, timeout - 1, Some(1))).await,
+        Err(DasError::ExpiredCertificate { .. })
+    ));
+    assert!(matches!(
+        decode_das_batch(&reader, HEADER, &certificate(0x88, tree_hash, timeout, Some(2))).await,
+        Err(DasError::UnsupportedVersion(2))
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use alloy::{
+    consensus::{SignableTransaction, TxEip1559, TxEnvelope},
+    dyn_abi::DynSolValue,
+    eips::eip2718::Encodable2718,
+    network::TxSignerSync,
+    primitives::{address, aliases::U24, Address, Bytes, Selector, TxKind, U160, U256},
+    signers::local::PrivateKeySigner,
+    sol_types::SolCall,
+};
+use indexer_rs::{
+    decoder::{
+        AbiDecoder, AbiLoadError, CalldataDecoder, DecodedCall, DecoderError, DecoderRegistry, FunctionCall, Route,
+    },
+    nitro::tx::{decode_signed_tx, L2Transaction},
+    sequencer_inbox::SequencerInbox,
+    uniswap::{
+        self,
+        decoder::pool_factory,
+        path::UNISWAP_V3_FACTORY,
+        position_manager::POSITION_MANAGER,
+        router::{IMulticall, IV3SwapRouter, RouterVersion, MSG_SENDER, SWAP_ROUTER_02},
+    },
+};
+
+const WETH: Address = address!("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1");
+const USDC: Address = address!("0xaf88d065e77c8cC2239327C5EDb3A432268e5831");
+/// A contract no decoder is registered for, such as a fork keeping the SwapRouter02 ABI.
+const FORK_ROUTER: Address = address!("0x00000000000000000000000000000000000f0f0f");
+
+fn sign(to: Address, input: Bytes) -> L2Transaction {
+    let signer = PrivateKeySigner::random();
+    let mut tx = TxEip1559 {
+        chain_id: 42161,
+        nonce: 0,
+        gas_limit: 500_000,
+        max_fee_per_gas: 100_000_000,
+        max_priority_fee_per_gas: 0,
+        to: TxKind::Call(to),
+        value: U256::ZERO,
+        access_list: Default::default(),
+        input,
+    };
+    let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+    decode_signed_tx(&TxEnvelope::from(tx.into_signed(signature)).encoded_2718()).unwrap()
+}
+
+fn exact_input_single() -> Bytes {
+    IV3SwapRouter::exactInputSingleCall {
+        params: IV3SwapRouter::ExactInputSingleParams {
+            tokenIn: WETH,
+            tokenOut: USDC,
+            fee: U24::from(500),
+            recipient: MSG_SENDER,
+            amountIn: U256::from(10u64.pow(17)),
+            amountOutMinimum: U256::from(1),
+            sqrtPriceLimitX96: U160::ZERO,
+        },
+    }
+    .abi_encode()
+    .into()
+}
+
+#[test]
+fn routes_by_deployment_and_falls_back_on_selector() {
+    let registry = DecoderRegistry::new(uniswap::decoder::decoders());
+    assert_eq!(registry.len(), 3);
+
+    let [output] = <[_; 1]>::try_from(registry.decode(&sign(SWAP_ROUTER_02, exact_input_single()))).unwrap();
+    assert_eq!((output.decoder, output.route), ("uniswap-v3-router", Route::Deployment));
+    assert_eq!(pool_factory(output.route), Some(UNISWAP_V3_FACTORY));
+    assert!(matches!(&output.calls.unwrap()[..], [DecodedCall::Swap(s)] if s.router == RouterVersion::SwapRouter02));
+
+    // A fork keeping the ABI is decoded through its selectors, but its pools are not Uniswap's.
+    let [output] = <[_; 1]>::try_from(registry.decode(&sign(FORK_ROUTER, exact_input_single()))).unwrap();
+    assert_eq!((output.decoder, output.route), ("uniswap-v3-router", Route::Selector));
+    assert_eq!(pool_factory(output.route), None);
+    assert!(matches!(&output.calls.unwrap()[..], [DecodedCall::Swap(s)] if s.path == [WETH, USDC]));
+
+    // A known deployment only reaches its own decoders.
+    assert!(registry.decode(&sign(POSITION_MANAGER, exact_input_single())).is_empty());
+    assert!(registry.decode(&sign(FORK_ROUTER, vec![0xa9, 0x05, 0x9c, 0xbb].into())).is_empty());
+
+    // multicall(bytes[]) is shared by the routers and the position manager.
+    let multicall = IMulticall::multicallCall { data: vec![exact_input_single()] }.abi_encode();
+    let outputs = registry.decode(&sign(FORK_ROUTER, multicall.into()));
+    assert_eq!(outputs.iter().map(|o| o.decoder).collect::<Vec<_>>(), ["uniswap-v3-router", "uniswap-v3-positions"]);
+    assert_eq!(outputs[0].calls.as_ref().unwrap().len(), 1);
+    assert_eq!(outputs[1].calls.as_ref().unwrap(), &[]);
+}
+
+#[test]
+fn loads_decoders_from_json_abis() {
+    let inbox = address!("0x1c479675ad559DC151F6Ec7ed3FbF8ceE79582B6");
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/abi/SequencerInbox.json");
+    let decoder = AbiDecoder::from_file(&path, vec![inbox]).unwrap();
+    assert_eq!(decoder.name(), "SequencerInbox");
+    assert!(decoder.selectors().contains(&Selector::from(SequencerInbox::addSequencerL2BatchCall::SELECTOR)));
+
+    let input = SequencerInbox::addSequencerL2BatchCall {
+        sequenceNumber: U256::from(42),
+        data: Bytes::from_static(&[0x00, 0x1b]),
+        afterDelayedMessagesRead: U256::from(7),
+        gasRefunder: Address::ZERO,
+        prevMessageCount: U256::from(100),
+        newMessageCount: U256::from(120),
+    }
+    .abi_encode();
+    let [DecodedCall::Function(FunctionCall { name, signature, args })] = &decoder.decode(&input).unwrap()[..] else {
+        panic!("expected one function call");
+    };
+    assert_eq!(name, "addSequencerL2Batch");
+    assert_eq!(signature, "addSequencerL2Batch(uint256,bytes,uint256,address,uint256,uint256)");
+    assert_eq!(args[0], DynSolValue::Uint(U256::from(42), 256));
+    assert_eq!(args[1], DynSolValue::Bytes(vec![0x00, 0x1b]));
+    assert!(matches!(decoder.decode(&[0xa9, 0x05, 0x9c, 0xbb]), Err(DecoderError::UnknownSelector(_))));
+    assert!(matches!(decoder.decode(&input[..40]), Err(DecoderError::Abi(_))));
+
+    // Compiler artifacts carry the ABI under `abi`.
+    let artifact = r#"{"abi":[{"type":"function","name":"claim","inputs":[{"name":"id","type":"uint256"}],"outputs":[],"stateMutability":"nonpayable"}],"bytecode":"0x00"}"#;
+    let decoder = AbiDecoder::from_json("rewards", artifact, Vec::new()).unwrap();
+    assert_eq!(decoder.selectors().len(), 1);
+    assert!(matches!(
+        AbiDecoder::from_json("empty", r#"{"bytecode":"0x00"}"#, Vec::new()),
+        Err(AbiLoadError::MissingAbi(_))
+    ));
+}
+
+/// A protocol decoder written outside the crate, as Camelot or GMX support would be.
+struct Claims {
+    addresses: Vec<Address>,
+}
+
+impl CalldataDecoder for Claims {
+    fn name(&self) -> &str {
+        "claims"
+    }
+
+    fn addresses(&self) -> &[Address] {
+        &self.addresses
+    }
+
+    fn selectors(&self) -> Vec<Selector> {
+        vec![Selector::from(IV3SwapRouter::exactInputSingleCall::SELECTOR)]
+    }
+
+    fn decode(&self, _input: &[u8]) -> Result<Vec<DecodedCall>, DecoderError> {
+        Ok(vec![DecodedCall::Function(FunctionCall {
+            name: "claim".into(),
+            signature: "claim()".into(),
+            args: Vec::new(),
+        })])
+    }
+}
+
+#[test]
+fn registers_decoders_without_changing_the_core() {
+    let deployment = address!("0x00000000000000000000000000000000c1a1a5e5");
+    let mut registry = DecoderRegistry::new(uniswap::decoder::decoders());
+    registry.register(Box::new(Claims { addresses: vec![deployment] }));
+
+    // Its own deployment routes only to it, although the selector is also a SwapRouter02 one.
+    let [output] = <[_; 1]>::try_from(registry.decode(&sign(deployment, exact_input_single()))).unwrap();
+    assert_eq!((output.decoder, output.route), ("claims", Route::Deployment));
+    // Unknown deployments reach every decoder of the selector, in registration order.
+    let outputs = registry.decode(&sign(FORK_ROUTER, exact_input_single()));
+    assert_eq!(outputs.iter().map(|o| o.decoder).collect::<Vec<_>>(), ["uniswap-v3-router", "claims"]);
+    assert_eq!(
+        registry.route(SWAP_ROUTER_02, Selector::from(IV3SwapRouter::exactInputSingleCall::SELECTOR)).1.len(),
+        1
+    );
+
+    let mut create = sign(deployment, exact_input_single());
+    create.to = None;
+    assert!(registry.decode(&create).is_empty());
+}
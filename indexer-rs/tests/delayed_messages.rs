@@ -0,0 +1,115 @@
+use alloy::primitives::{hex, keccak256, Address, B256, U256};
+use indexer_rs::nitro::{
+    batch::{BatchHeader, L2Message, Segment, SequencerBatch},
+    delayed::{
+        delayed_message_pointers, materialize_delayed_message, DelayedMessage, DelayedMessageError, DelayedPointer,
+        DelayedTx,
+    },
+};
+
+fn batch(after_delayed_messages: u64, segments: Vec<Segment>) -> SequencerBatch {
+    SequencerBatch {
+        header: BatchHeader {
+            min_timestamp: 0,
+            max_timestamp: u64::MAX,
+            min_l1_block: 0,
+            max_l1_block: u64::MAX,
+            after_delayed_messages,
+        },
+        flags: None,
+        segments,
+    }
+}
+
+fn message(index: u64, kind: u8, data: Vec<u8>) -> DelayedMessage {
+    DelayedMessage {
+        index,
+        kind,
+        sender: Address::repeat_byte(0x11),
+        l1_block: 19_000_000,
+        timestamp: 1_700_000_000,
+        base_fee_l1: U256::from(10),
+        data: data.into(),
+    }
+}
+
+#[test]
+fn maps_delayed_segments_and_trailing_messages() {
+    let segments = vec![
+        Segment::AdvanceTimestamp(1),
+        Segment::DelayedMessages,
+        Segment::L2Message { compressed: false, message: L2Message::Heartbeat },
+        Segment::DelayedMessages,
+    ];
+    let pointers = delayed_message_pointers(&batch(14, segments.clone()), 10).unwrap();
+    assert_eq!(
+        pointers,
+        vec![
+            DelayedPointer { segment: Some(1), message_index: 10 },
+            DelayedPointer { segment: Some(3), message_index: 11 },
+            DelayedPointer { segment: None, message_index: 12 },
+            DelayedPointer { segment: None, message_index: 13 },
+        ]
+    );
+
+    // A batch without data still consumes its delayed messages.
+    assert_eq!(delayed_message_pointers(&batch(2, Vec::new()), 1).unwrap().len(), 1);
+
+    assert_eq!(
+        delayed_message_pointers(&batch(11, segments), 10),
+        Err(DelayedMessageError::ReadPastBatchCount { segment: 3, after: 11 })
+    );
+    assert_eq!(
+        delayed_message_pointers(&batch(3, Vec::new()), 4),
+        Err(DelayedMessageError::CountBackwards { before: 4, after: 3 })
+    );
+}
+
+#[test]
+fn materializes_eth_deposits() {
+    let to = Address::repeat_byte(0x22);
+    let value = U256::from(1_000_000_000_000_000u64);
+    let mut data = to.to_vec();
+    data.extend_from_slice(&value.to_be_bytes::<32>());
+
+    let DelayedTx::EthDeposit { hash, tx } = materialize_delayed_message(&message(7, 12, data), 42161).unwrap() else {
+        panic!("expected an ETH deposit");
+    };
+    assert_eq!(tx.l1_request_id, B256::from(U256::from(7)));
+    assert_eq!((tx.from, tx.to, tx.value), (Address::repeat_byte(0x11), to, value));
+
+    // 0x64 || rlp([42161, requestId, from, to, 1e15])
+    let mut encoded = hex!("64f856" "82a4b1" "a0").to_vec();
+    encoded.extend_from_slice(tx.l1_request_id.as_slice());
+    encoded.push(0x94);
+    encoded.extend_from_slice(tx.from.as_slice());
+    encoded.push(0x94);
+    encoded.extend_from_slice(to.as_slice());
+    encoded.extend_from_slice(&hex!("87038d7ea4c68000"));
+    assert_eq!(hash, keccak256(encoded));
+
+    assert_eq!(
+        materialize_delayed_message(&message(7, 12, vec![0; 51]), 42161),
+        Err(DelayedMessageError::Truncated { index: 7, kind: 12, len: 51 })
+    );
+}
+
+#[test]
+fn materializes_other_message_kinds() {
+    let truncated_retryable = message(3, 9, vec![0xaa; 4]);
+    assert!(matches!(
+        materialize_delayed_message(&truncated_retryable, 42161),
+        Err(DelayedMessageError::Retryable { index: 3, .. })
+    ));
+
+    let signed = message(4, 3, vec![4, 0x02, 0xc0]);
+    assert!(matches!(
+        materialize_delayed_message(&signed, 42161).unwrap(),
+        DelayedTx::L2Message { message: L2Message::SignedTx(_), .. }
+    ));
+
+    assert_eq!(
+        materialize_delayed_message(&message(5, 6, Vec::new()), 42161).unwrap(),
+        DelayedTx::Ignored { kind: 6 }
+    );
+}
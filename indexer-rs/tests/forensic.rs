@@ -0,0 +1,58 @@
+use indexer_rs::nitro::{
+    batch::{BatchDecodeError, BatchHeader, Segment},
+    forensic::{analyze, ForensicInput, LengthEncoding},
+};
+
+const HEADER: BatchHeader = BatchHeader {
+    min_timestamp: 1_700_000_000,
+    max_timestamp: 1_700_000_600,
+    min_l1_block: 18_500_000,
+    max_l1_block: 18_500_050,
+    after_delayed_messages: 3,
+};
+
+/// `kind || u64 BE length || payload` segments, the legacy framing the scanner guesses.
+fn framed(segments: &[(u8, &[u8])]) -> Vec<u8> {
+    segments
+        .iter()
+        .flat_map(|(kind, payload)| {
+            let mut out = vec![*kind];
+            out.extend((payload.len() as u64).to_be_bytes());
+            out.extend_from_slice(payload);
+            out
+        })
+        .collect()
+}
+
+#[test]
+fn reports_candidate_parses_for_rejected_payloads() {
+    let mut payload = vec![0x07];
+    payload.extend(framed(&[(3, &[0x05]), (2, &[0x00])]));
+
+    let report = analyze(HEADER, &payload);
+    assert_eq!(report.strict.unwrap_err(), BatchDecodeError::UnknownHeaderByte(0x07));
+
+    let best = &report.candidates[0];
+    assert_eq!(best.input, ForensicInput::Raw);
+    assert_eq!(best.confidence, 1.0);
+    assert_eq!(best.bytes_covered, best.input_len);
+    let decoded: Vec<_> = best.segments.iter().map(|s| s.decoded.clone().unwrap()).collect();
+    assert_eq!(decoded, vec![Segment::AdvanceTimestamp(5), Segment::DelayedMessages]);
+    assert!(best.segments.iter().all(|s| s.encoding == LengthEncoding::U64Be && s.skipped == 0));
+    assert!(report.candidates.windows(2).all(|w| w[0].confidence >= w[1].confidence));
+}
+
+#[test]
+fn penalises_resynchronisation() {
+    let mut payload = vec![0x07, 0xff, 0xff, 0xff];
+    payload.extend(framed(&[(3, &[0x05])]));
+
+    let report = analyze(HEADER, &payload);
+    let best = &report.candidates[0];
+    assert_eq!(best.segments[0].skipped, 3);
+    assert_eq!(best.segments[0].offset, 3);
+    assert!(best.confidence < 1.0 && best.confidence > 0.0);
+
+    // Nothing plausible at all: no candidates, only the strict verdict.
+    assert!(analyze(HEADER, &[0x07, 0xff, 0xff]).candidates.is_empty());
+}
@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use alloy::eips::eip4844::Bytes48;
+use c_kzg::{ethereum_kzg_settings, Blob};
+use indexer_rs::nitro::{
+    blob::{encode_blobs, BYTES_PER_BLOB},
+    kzg::{KzgError, KzgVerifier},
+};
+
+fn blob_proof(blob: &[u8], commitment: &Bytes48) -> Bytes48 {
+    let settings = ethereum_kzg_settings(0);
+    let proof = settings
+        .compute_blob_kzg_proof(&Blob::from_bytes(blob).unwrap(), &c_kzg::Bytes48::new(commitment.0))
+        .unwrap();
+    Bytes48::from_slice(proof.as_ref())
+}
+
+#[test]
+fn commits_with_the_embedded_or_a_file_setup() {
+    let blobs = encode_blobs(b"nitro batch");
+    let embedded = KzgVerifier::embedded().commitments(&blobs).unwrap();
+    let setup = Path::new(env!("CARGO_MANIFEST_DIR")).join("trusted_setup.txt");
+    assert_eq!(KzgVerifier::from_file(setup).unwrap().commitments(&blobs).unwrap(), embedded);
+
+    // The all-zero blob commits to the point at infinity.
+    let zero = KzgVerifier::embedded().commitments(&[vec![0u8; BYTES_PER_BLOB]]).unwrap();
+    assert_eq!(zero[0][0], 0xc0);
+    assert!(zero[0][1..].iter().all(|b| *b == 0));
+
+    assert!(matches!(KzgVerifier::from_file("/nonexistent/trusted_setup.txt"), Err(KzgError::Setup { .. })));
+}
+
+#[test]
+fn refuses_malformed_blob_sizes() {
+    let kzg = KzgVerifier::embedded();
+    let mut short = encode_blobs(b"nitro batch");
+    short[0].truncate(BYTES_PER_BLOB - 1);
+    assert_eq!(kzg.commitments(&short), Err(KzgError::BlobSize { index: 0, len: BYTES_PER_BLOB - 1 }));
+
+    let long = vec![vec![0u8; BYTES_PER_BLOB], vec![0u8; BYTES_PER_BLOB + 32]];
+    assert_eq!(kzg.commitments(&long), Err(KzgError::BlobSize { index: 1, len: BYTES_PER_BLOB + 32 }));
+}
+
+#[test]
+fn verifies_blob_proofs_in_batch() {
+    let kzg = KzgVerifier::embedded();
+    let payload: Vec<u8> = (0..200_000u32).map(|i| (i % 253) as u8).collect();
+    let blobs = encode_blobs(&payload);
+    assert_eq!(blobs.len(), 2);
+    let commitments = kzg.commitments(&blobs).unwrap();
+    let proofs: Vec<Bytes48> = blobs.iter().zip(&commitments).map(|(b, c)| blob_proof(b, c)).collect();
+
+    assert_eq!(kzg.verify_proofs(&blobs, &commitments, &proofs), Ok(()));
+
+    let swapped = [proofs[1], proofs[0]];
+    assert_eq!(kzg.verify_proofs(&blobs, &commitments, &swapped), Err(KzgError::InvalidProof));
+    assert_eq!(
+        kzg.verify_proofs(&blobs, &commitments, &proofs[..1]),
+        Err(KzgError::CountMismatch { blobs: 2, found: 1, what: "proof(s)" })
+    );
+}
@@ -0,0 +1,88 @@
+use alloy::{
+    consensus::{SignableTransaction, TxEip1559, TxEip2930, TxEnvelope, TxLegacy, TxType},
+    eips::eip2718::Encodable2718,
+    network::TxSignerSync,
+    primitives::{address, Bytes, TxKind, U256},
+    signers::local::PrivateKeySigner,
+};
+use indexer_rs::nitro::tx::{decode_signed_tx, TxDecodeError};
+
+const ROUTER: alloy::primitives::Address = address!("0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45");
+
+fn sign<T>(signer: &PrivateKeySigner, mut tx: T) -> Vec<u8>
+where
+    T: SignableTransaction<alloy::primitives::Signature>,
+    TxEnvelope: From<alloy::consensus::Signed<T>>,
+{
+    let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+    TxEnvelope::from(tx.into_signed(signature)).encoded_2718()
+}
+
+#[test]
+fn decodes_every_signed_tx_type() {
+    let signer = PrivateKeySigner::random();
+    let input = Bytes::from_static(&[0x41, 0x4b, 0xf3, 0x89]);
+
+    let pre_eip155 = TxLegacy {
+        chain_id: None,
+        nonce: 1,
+        gas_price: 100_000_000,
+        gas_limit: 21_000,
+        to: TxKind::Call(ROUTER),
+        value: U256::from(5),
+        input: input.clone(),
+    };
+    let eip155 = TxLegacy { chain_id: Some(42161), ..pre_eip155.clone() };
+    let eip2930 = TxEip2930 {
+        chain_id: 42161,
+        nonce: 2,
+        gas_price: 100_000_000,
+        gas_limit: 50_000,
+        to: TxKind::Call(ROUTER),
+        value: U256::ZERO,
+        access_list: Default::default(),
+        input: input.clone(),
+    };
+    let eip1559 = TxEip1559 {
+        chain_id: 42161,
+        nonce: 3,
+        gas_limit: 50_000,
+        max_fee_per_gas: 100_000_000,
+        max_priority_fee_per_gas: 0,
+        to: TxKind::Create,
+        value: U256::ZERO,
+        access_list: Default::default(),
+        input: input.clone(),
+    };
+
+    let cases = [
+        (sign(&signer, pre_eip155), TxType::Legacy, None, 1),
+        (sign(&signer, eip155), TxType::Legacy, Some(42161), 1),
+        (sign(&signer, eip2930), TxType::Eip2930, Some(42161), 2),
+        (sign(&signer, eip1559), TxType::Eip1559, Some(42161), 3),
+    ];
+    for (raw, tx_type, chain_id, nonce) in cases {
+        let tx = decode_signed_tx(&raw).unwrap();
+        assert_eq!(tx.tx_type, tx_type);
+        assert_eq!(tx.chain_id, chain_id);
+        assert_eq!(tx.from, signer.address());
+        assert_eq!(tx.nonce, nonce);
+        assert_eq!(tx.input, input);
+        assert_eq!(tx.hash, alloy::primitives::keccak256(&raw));
+        if tx_type == TxType::Eip1559 {
+            assert_eq!(tx.to, None);
+        } else {
+            assert_eq!(tx.to, Some(ROUTER));
+        }
+    }
+}
+
+#[test]
+fn rejects_malformed_signed_txs() {
+    let signer = PrivateKeySigner::random();
+    let mut raw = sign(&signer, TxLegacy { chain_id: Some(1), gas_limit: 21_000, ..Default::default() });
+    raw.push(0x00);
+    assert!(matches!(decode_signed_tx(&raw), Err(TxDecodeError::TrailingBytes(1))));
+    assert!(matches!(decode_signed_tx(&[]), Err(TxDecodeError::Empty)));
+    assert!(matches!(decode_signed_tx(&[0x64, 0xc0]), Err(TxDecodeError::Encoding(_))));
+}
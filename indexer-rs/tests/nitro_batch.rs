@@ -0,0 +1,178 @@
+use std::io::Write;
+
+use alloy::primitives::{Address, Bytes, B256, U256};
+use indexer_rs::nitro::batch::{
+    decode_l2_message, decode_segments, parse_blob_hashes, BatchDecodeError, BatchHeader, HeaderFlags, L2Message,
+    L2MessageError, Segment, SequencerBatch, MAX_SEGMENTS_PER_SEQUENCER_MESSAGE,
+};
+
+const HEADER: BatchHeader = BatchHeader {
+    min_timestamp: 1_700_000_000,
+    max_timestamp: 1_700_000_600,
+    min_l1_block: 18_500_000,
+    max_l1_block: 18_500_050,
+    after_delayed_messages: 1_234_567,
+};
+
+fn brotli(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut out, 4096, 11, 22);
+        writer.write_all(data).unwrap();
+    }
+    out
+}
+
+fn rlp_stream(items: &[Vec<u8>]) -> Vec<u8> {
+    items.iter().flat_map(|item| alloy::rlp::encode(&item[..])).collect()
+}
+
+fn sequencer_payload(segments: &[Vec<u8>]) -> Vec<u8> {
+    let mut payload = vec![0x00];
+    payload.extend(brotli(&rlp_stream(segments)));
+    payload
+}
+
+fn nested_batch(messages: &[&[u8]]) -> Vec<u8> {
+    let mut out = vec![3u8];
+    for m in messages {
+        out.extend((m.len() as u64).to_be_bytes());
+        out.extend_from_slice(m);
+    }
+    out
+}
+
+#[test]
+fn header_round_trips() {
+    let mut message = HEADER.to_bytes().to_vec();
+    message.extend([0xaa, 0xbb]);
+    let (header, rest) = BatchHeader::parse(&message).unwrap();
+    assert_eq!(header, HEADER);
+    assert_eq!(rest, &[0xaa, 0xbb]);
+    assert_eq!(BatchHeader::parse(&message[..39]), Err(BatchDecodeError::HeaderTooShort(39)));
+}
+
+#[test]
+fn decodes_segments_into_typed_messages() {
+    let tx_a = [0x02, 0xf8, 0x01];
+    let tx_b = [0xf8, 0x6b, 0x80];
+    let signed = |tx: &[u8]| [&[4u8][..], tx].concat();
+
+    let mut segments = vec![
+        [&[0u8][..], &signed(&tx_a)].concat(),
+        vec![],
+        [&[1u8][..], &brotli(&nested_batch(&[&signed(&tx_b), &[6u8]]))].concat(),
+        vec![2],
+        [&[3u8][..], &alloy::rlp::encode(12u64)].concat(),
+        [&[4u8][..], &alloy::rlp::encode(1u64)].concat(),
+    ];
+    let mut unsigned = vec![0u8];
+    for word in [21_000u64, 100, 7, 0, 5] {
+        unsigned.extend(U256::from(word).to_be_bytes::<32>());
+    }
+    unsigned[1 + 3 * 32 + 31] = 0x11; // `to` address
+    unsigned.extend([0xde, 0xad]);
+    segments.push([&[0u8][..], &unsigned].concat());
+
+    let mut message = HEADER.to_bytes().to_vec();
+    message.extend(sequencer_payload(&segments));
+    let batch = SequencerBatch::decode_sequencer_message(&message).unwrap();
+
+    assert_eq!(batch.header, HEADER);
+    assert_eq!(
+        batch.segments[..5],
+        [
+            Segment::L2Message { compressed: false, message: L2Message::SignedTx(Bytes::from(tx_a.to_vec())) },
+            Segment::L2Message {
+                compressed: true,
+                message: L2Message::Batch(vec![
+                    L2Message::SignedTx(Bytes::from(tx_b.to_vec())),
+                    L2Message::Heartbeat,
+                ]),
+            },
+            Segment::DelayedMessages,
+            Segment::AdvanceTimestamp(12),
+            Segment::AdvanceL1BlockNumber(1),
+        ]
+    );
+    let Segment::L2Message { message: L2Message::UnsignedUserTx(tx), .. } = &batch.segments[5] else {
+        panic!("expected an unsigned user tx, got {:?}", batch.segments[5]);
+    };
+    assert_eq!(tx.gas_limit, U256::from(21_000));
+    assert_eq!(tx.nonce, Some(U256::from(7)));
+    assert_eq!(tx.to, Some(Address::with_last_byte(0x11)));
+    assert_eq!(tx.data, Bytes::from_static(&[0xde, 0xad]));
+
+    assert_eq!(batch.signed_txs(), vec![&Bytes::from(tx_a.to_vec()), &Bytes::from(tx_b.to_vec())]);
+    assert_eq!(batch.delayed_message_count(), 1);
+}
+
+#[test]
+fn reports_structured_errors() {
+    assert_eq!(SequencerBatch::decode(HEADER, &[]), Err(BatchDecodeError::EmptyPayload));
+    assert_eq!(SequencerBatch::decode(HEADER, &[0x80; 65]), Err(BatchDecodeError::DasCertificate(0x80)));
+
+    // Second item is an RLP list, which is not a valid segment.
+    let mut stream = rlp_stream(&[vec![2]]);
+    stream.extend([0xc1, 0x01]);
+    let mut payload = vec![0x00];
+    payload.extend(brotli(&stream));
+    assert!(matches!(
+        SequencerBatch::decode(HEADER, &payload),
+        Err(BatchDecodeError::SegmentRlp { offset: 1, .. })
+    ));
+
+    // An advance segment whose value is not an RLP integer, after a 2-byte delayed segment.
+    let mut payload = vec![0x00];
+    payload.extend(brotli(&rlp_stream(&[vec![2], vec![3, 0xc0]])));
+    assert!(matches!(
+        SequencerBatch::decode(HEADER, &payload),
+        Err(BatchDecodeError::InvalidAdvance { index: 1, offset: 1, .. })
+    ));
+
+    // Empty segments are skipped, but still count for the indices and the segment limit.
+    let mut payload = vec![0x00];
+    payload.extend(brotli(&rlp_stream(&[vec![], vec![2], vec![3, 0xc0]])));
+    assert!(matches!(
+        SequencerBatch::decode(HEADER, &payload),
+        Err(BatchDecodeError::InvalidAdvance { index: 2, offset: 2, .. })
+    ));
+    let empty = vec![0x80; MAX_SEGMENTS_PER_SEQUENCER_MESSAGE];
+    assert_eq!(decode_segments(&empty).unwrap(), []);
+    assert_eq!(decode_segments(&[&empty[..], &[0x80]].concat()), Err(BatchDecodeError::TooManySegments));
+
+    let truncated = [&nested_batch(&[&[4, 1, 2]])[..], &[0, 0, 0]].concat();
+    assert!(matches!(
+        decode_l2_message(&truncated),
+        Err(L2MessageError::BatchFrame { offset: 11, .. })
+    ));
+    assert_eq!(decode_l2_message(&[]), Err(L2MessageError::Empty));
+}
+
+#[test]
+fn recognises_every_header_flag() {
+    let names = |flag: u8| HeaderFlags(flag).names();
+    assert_eq!(names(0x00), ["brotli"]);
+    assert_eq!(names(0x80), ["das"]);
+    assert_eq!(names(0x88), ["das", "tree-das"]);
+    assert_eq!(names(0x50), ["blob-hashes"]);
+    assert_eq!(names(0x40), ["l1-authenticated"]);
+    assert_eq!(names(0x20), ["zero-heavy"]);
+    assert_eq!(names(0x41), ["l1-authenticated", "unknown"]);
+    assert_eq!(HeaderFlags(0x88).to_string(), "0x88 (das|tree-das)");
+
+    let mut brotli_payload = vec![0x00];
+    brotli_payload.extend(brotli(&rlp_stream(&[vec![2]])));
+    assert_eq!(SequencerBatch::decode(HEADER, &brotli_payload).unwrap().flags, Some(HeaderFlags::BROTLI));
+
+    let decode = |payload: &[u8]| SequencerBatch::decode(HEADER, payload);
+    assert_eq!(decode(&[0x88; 130]), Err(BatchDecodeError::DasCertificate(0x88)));
+    assert_eq!(decode(&[&[0x50][..], &[0x01; 64]].concat()), Err(BatchDecodeError::BlobHashes(2)));
+    assert_eq!(decode(&[0x50; 34]), Err(BatchDecodeError::MalformedBlobHashes(33)));
+    assert_eq!(decode(&[0x20, 0x00]), Err(BatchDecodeError::ZeroheavyNotSupported(0x20)));
+    assert_eq!(decode(&[0x41, 0x00]), Err(BatchDecodeError::UnknownAuthenticatedHeaderByte(0x41)));
+    assert_eq!(decode(&[0x03, 0x00]), Err(BatchDecodeError::UnknownHeaderByte(0x03)));
+
+    let hashes = parse_blob_hashes(&[&[0x50][..], &[0x01; 32], &[0x02; 32]].concat()).unwrap();
+    assert_eq!(hashes, vec![B256::repeat_byte(0x01), B256::repeat_byte(0x02)]);
+}
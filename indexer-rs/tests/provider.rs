@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use alloy::{
+    primitives::U64,
+    providers::{
+        mock::{Asserter, MockTransport},
+        Provider,
+    },
+    rpc::json_rpc::{ErrorPayload, RequestPacket},
+    transports::{BoxTransport, TransportErrorKind, TransportFut},
+};
+use indexer_rs::provider::{is_endpoint_failure, FailoverTransport, RetryPolicy};
+
+const POLICY: RetryPolicy = RetryPolicy {
+    max_retries: 0,
+    initial_backoff: Duration::from_millis(1),
+    max_backoff: Duration::from_millis(4),
+    failure_threshold: 1,
+    cooldown: Duration::from_secs(60),
+};
+
+fn error(code: i64, message: &'static str) -> ErrorPayload {
+    ErrorPayload { code, message: message.into(), data: None }
+}
+
+/// An endpoint answering every request with HTTP `status`.
+fn http_status(status: u16) -> BoxTransport {
+    BoxTransport::new(tower::service_fn(move |_: RequestPacket| -> TransportFut<'static> {
+        Box::pin(async move { Err(TransportErrorKind::http_error(status, "unavailable".to_string())) })
+    }))
+}
+
+fn mock(asserter: &Asserter) -> BoxTransport {
+    BoxTransport::new(MockTransport::new(asserter.clone()))
+}
+
+#[test]
+fn backs_off_exponentially_with_jitter() {
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_secs(1),
+        ..RetryPolicy::default()
+    };
+    for _ in 0..20 {
+        let first = policy.backoff(0);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100), "{first:?}");
+        let fourth = policy.backoff(3);
+        assert!(fourth >= Duration::from_millis(400) && fourth <= Duration::from_millis(800), "{fourth:?}");
+        let capped = policy.backoff(40);
+        assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_secs(1), "{capped:?}");
+    }
+
+    assert!(is_endpoint_failure(&TransportErrorKind::http_error(429, String::new())));
+    assert!(is_endpoint_failure(&TransportErrorKind::http_error(502, String::new())));
+    assert!(!is_endpoint_failure(&TransportErrorKind::http_error(400, String::new())));
+    assert!(is_endpoint_failure(&alloy::transports::RpcError::ErrorResp(error(429, "Too Many Requests"))));
+    assert!(!is_endpoint_failure(&alloy::transports::RpcError::ErrorResp(error(-32000, "execution reverted"))));
+}
+
+#[tokio::test]
+async fn fails_over_and_tracks_endpoint_health() {
+    let (rate_limited, healthy) = (Asserter::new(), Asserter::new());
+    let transport = FailoverTransport::new(
+        vec![
+            ("down".to_string(), http_status(503)),
+            ("rate-limited".to_string(), mock(&rate_limited)),
+            ("healthy".to_string(), mock(&healthy)),
+        ],
+        POLICY,
+    );
+    let provider = transport.clone().into_provider();
+
+    rate_limited.push_failure(error(429, "Too Many Requests"));
+    healthy.push_success(&U64::from(19_000_000));
+    assert_eq!(provider.get_block_number().await.unwrap(), 19_000_000);
+
+    let health = transport.health();
+    assert_eq!(health.iter().map(|(_, h)| (h.successes, h.failures)).collect::<Vec<_>>(), [(0, 1), (0, 1), (1, 0)]);
+    assert!(health[0].1.unhealthy_until.is_some() && health[2].1.unhealthy_until.is_none());
+
+    // Unhealthy endpoints are tried last, so the next request goes straight to the healthy one.
+    healthy.push_success(&U64::from(19_000_001));
+    assert_eq!(provider.get_block_number().await.unwrap(), 19_000_001);
+    assert_eq!(transport.health()[0].1.failures, 1);
+
+    // A JSON-RPC error about the request itself is returned, not failed over.
+    healthy.push_failure(error(-32000, "execution reverted"));
+    rate_limited.push_success(&U64::ZERO);
+    assert!(provider.get_block_number().await.is_err());
+    assert_eq!(rate_limited.read_q().len(), 1);
+    assert_eq!(transport.health()[2].1.consecutive_failures, 0);
+}
+
+#[tokio::test]
+async fn retries_after_every_endpoint_failed() {
+    let asserter = Asserter::new();
+    let policy = RetryPolicy { max_retries: 2, failure_threshold: 5, ..POLICY };
+    let transport = FailoverTransport::new(vec![("only".to_string(), mock(&asserter))], policy);
+    let provider = transport.clone().into_provider();
+
+    asserter.push_failure(error(429, "Too Many Requests"));
+    asserter.push_failure(error(429, "Too Many Requests"));
+    asserter.push_success(&U64::from(7));
+    assert_eq!(provider.get_block_number().await.unwrap(), 7);
+    assert_eq!(transport.health()[0].1.failures, 2);
+
+    for _ in 0..3 {
+        asserter.push_failure(error(429, "Too Many Requests"));
+    }
+    assert!(provider.get_block_number().await.is_err());
+}
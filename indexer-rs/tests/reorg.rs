@@ -0,0 +1,98 @@
+use alloy::{
+    primitives::{B256, U64},
+    providers::{mock::Asserter, ProviderBuilder},
+    rpc::types::Block,
+};
+use indexer_rs::{
+    checkpoint::Checkpoint,
+    reorg::{BatchStatus, ChainHeads, ReorgTracker},
+};
+
+fn checkpoint(seq: u64, l1_block: u64) -> Checkpoint {
+    Checkpoint { batch_sequence_number: seq, l1_block, log_index: 0, after_delayed_messages: seq * 2, after_acc: None }
+}
+
+fn block(number: u64) -> Block {
+    let mut block: Block = Block::default();
+    block.header.inner.number = number;
+    block
+}
+
+fn hash(l1_block: u64, fork: u8) -> B256 {
+    let mut hash = B256::left_padding_from(&l1_block.to_be_bytes());
+    hash[0] = fork;
+    hash
+}
+
+#[tokio::test]
+async fn fetches_head_safe_and_finalized_blocks() {
+    let asserter = Asserter::new();
+    let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+    asserter.push_success(&U64::from(1_120));
+    asserter.push_success(&block(1_090));
+    asserter.push_success(&block(1_060));
+    assert_eq!(
+        ChainHeads::fetch(&provider).await.unwrap(),
+        ChainHeads { head: 1_120, safe: Some(1_090), finalized: Some(1_060) }
+    );
+}
+
+#[test]
+fn promotes_batches_as_the_chain_advances() {
+    let resume = checkpoint(9, 990);
+    let mut tracker = ReorgTracker::new(12, Some(resume));
+    assert_eq!(tracker.record(checkpoint(10, 1_000), hash(1_000, 0), Some(resume)), BatchStatus::Tentative);
+    assert_eq!(
+        tracker.record(checkpoint(11, 1_010), hash(1_010, 0), Some(checkpoint(10, 1_000))),
+        BatchStatus::Tentative
+    );
+    assert_eq!(tracker.durable(), Some(resume));
+
+    assert_eq!(
+        tracker.update(ChainHeads { head: 1_015, safe: None, finalized: None }),
+        vec![(10, BatchStatus::Confirmed)]
+    );
+    assert_eq!(tracker.durable(), Some(checkpoint(10, 1_000)));
+
+    let promoted = tracker.update(ChainHeads { head: 1_040, safe: Some(1_010), finalized: Some(1_000) });
+    assert_eq!(promoted, vec![(10, BatchStatus::Finalized), (11, BatchStatus::Safe)]);
+    // Finalized batches are no longer tracked, but still back the durable checkpoint.
+    assert!(tracker.get(10).is_none());
+    assert_eq!(tracker.get(11).unwrap().status, BatchStatus::Safe);
+    assert_eq!(tracker.durable(), Some(checkpoint(11, 1_010)));
+
+    // Once heads are known, new batches start at the right status.
+    assert_eq!(
+        tracker.record(checkpoint(12, 1_035), hash(1_035, 0), Some(checkpoint(11, 1_010))),
+        BatchStatus::Tentative
+    );
+    assert_eq!(
+        tracker.record(checkpoint(13, 1_020), hash(1_020, 0), Some(checkpoint(12, 1_035))),
+        BatchStatus::Confirmed
+    );
+}
+
+#[test]
+fn replays_a_reorg() {
+    let mut tracker = ReorgTracker::new(12, None);
+    tracker.update(ChainHeads { head: 1_000, safe: Some(980), finalized: Some(950) });
+    let mut cursor = None;
+    for (seq, l1_block) in [(20, 990), (21, 995), (22, 998)] {
+        tracker.record(checkpoint(seq, l1_block), hash(l1_block, 0), cursor);
+        cursor = Some(checkpoint(seq, l1_block));
+    }
+
+    // The node removes batch 21's log with the old fork's block hash; a stale hash is ignored.
+    assert_eq!(tracker.remove(21, hash(995, 1)), None);
+    let rollback = tracker.remove(21, hash(995, 0)).unwrap();
+    assert_eq!(rollback.cursor, Some(checkpoint(20, 990)));
+    assert_eq!(rollback.from_block, 995);
+    let orphaned: Vec<u64> = rollback.orphaned.iter().map(|b| b.checkpoint.batch_sequence_number).collect();
+    assert_eq!(orphaned, vec![21, 22]);
+    assert!(tracker.get(22).is_none());
+
+    // The new fork re-delivers batch 21 in a different block.
+    assert_eq!(tracker.record(checkpoint(21, 996), hash(996, 1), rollback.cursor), BatchStatus::Tentative);
+    assert_eq!(tracker.get(21).unwrap().block_hash, hash(996, 1));
+    assert_eq!(tracker.remove(22, hash(998, 0)), None);
+}
@@ -0,0 +1,141 @@
+use alloy::primitives::{hex, keccak256, Address, B256, U256};
+use indexer_rs::nitro::{
+    delayed::{materialize_delayed_message, DelayedMessage, DelayedTx},
+    retryable::{decode_submit_retryable, RetryableError, SubmitRetryable, SubmitRetryableTx},
+};
+
+const ROUTER: Address = Address::repeat_byte(0x68);
+
+fn word(value: U256) -> [u8; 32] {
+    value.to_be_bytes()
+}
+
+fn address_word(address: Address) -> [u8; 32] {
+    address.into_word().0
+}
+
+fn payload(to: Address, gas_limit: U256, data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend(address_word(to));
+    out.extend(word(U256::ZERO)); // l2CallValue
+    out.extend(word(U256::from(1_000_000_000_000_000u64))); // deposit
+    out.extend(word(U256::ZERO)); // maxSubmissionFee
+    out.extend(address_word(Address::repeat_byte(0xfe)));
+    out.extend(address_word(Address::repeat_byte(0xcb)));
+    out.extend(word(gas_limit));
+    out.extend(word(U256::from(100_000_000u64))); // maxFeePerGas
+    out.extend(word(U256::from(data.len())));
+    out.extend_from_slice(data);
+    out
+}
+
+#[test]
+fn decodes_submit_retryable_fields() {
+    let retryable = decode_submit_retryable(&payload(ROUTER, U256::from(100_000), &[0x12, 0x34])).unwrap();
+    assert_eq!(
+        retryable,
+        SubmitRetryable {
+            to: Some(ROUTER),
+            l2_call_value: U256::ZERO,
+            deposit: U256::from(1_000_000_000_000_000u64),
+            max_submission_fee: U256::ZERO,
+            excess_fee_refund_address: Address::repeat_byte(0xfe),
+            call_value_refund_address: Address::repeat_byte(0xcb),
+            gas_limit: 100_000,
+            max_fee_per_gas: U256::from(100_000_000u64),
+            data: vec![0x12, 0x34].into(),
+        }
+    );
+
+    // The zero address means contract creation.
+    let creation = decode_submit_retryable(&payload(Address::ZERO, U256::from(1), &[])).unwrap();
+    assert_eq!(creation.to, None);
+}
+
+#[test]
+fn computes_ticket_id_and_auto_redeem_hash() {
+    let sender = Address::repeat_byte(0x11);
+    let message = DelayedMessage {
+        index: 7,
+        kind: 9,
+        sender,
+        l1_block: 19_000_000,
+        timestamp: 1_700_000_000,
+        base_fee_l1: U256::from(10),
+        data: payload(ROUTER, U256::from(100_000), &[0x12, 0x34]).into(),
+    };
+    let DelayedTx::SubmitRetryable { ticket_id, tx } = materialize_delayed_message(&message, 42161).unwrap() else {
+        panic!("expected a retryable submission");
+    };
+    assert_eq!(tx.request_id, B256::from(U256::from(7)));
+    assert_eq!((tx.from, tx.l1_base_fee), (sender, U256::from(10)));
+
+    // 0x69 || rlp([chainId, requestId, from, l1BaseFee, deposit, gasFeeCap, gas, retryTo,
+    //              retryValue, beneficiary, maxSubmissionFee, feeRefundAddr, retryData])
+    let mut encoded = hex!("69f88f" "82a4b1" "a0").to_vec();
+    encoded.extend_from_slice(tx.request_id.as_slice());
+    encoded.push(0x94);
+    encoded.extend_from_slice(sender.as_slice());
+    encoded.extend_from_slice(&hex!("0a" "87038d7ea4c68000" "8405f5e100" "830186a0" "94"));
+    encoded.extend_from_slice(ROUTER.as_slice());
+    encoded.extend_from_slice(&hex!("80" "94"));
+    encoded.extend_from_slice(Address::repeat_byte(0xcb).as_slice());
+    encoded.extend_from_slice(&hex!("80" "94"));
+    encoded.extend_from_slice(Address::repeat_byte(0xfe).as_slice());
+    encoded.extend_from_slice(&hex!("821234"));
+    assert_eq!(ticket_id, keccak256(encoded));
+    assert_eq!(ticket_id, tx.hash());
+
+    // The auto-redeem in a block with a 0.01 gwei base fee: the submission fee (10 * (1400 + 6 * 2))
+    // and the gas cost stay refundable, the gas price difference is refunded at submission.
+    let redeem = tx.auto_redeem(U256::from(10_000_000));
+    assert_eq!((redeem.nonce, redeem.ticket_id, redeem.submission_fee_refund), (0, ticket_id, U256::from(14_120)));
+    assert_eq!(redeem.max_refund, U256::from(991_000_000_000_000u64));
+    // 0x68 || rlp([chainId, nonce, from, gasFeeCap, gas, to, value, data, ticketId, refundTo,
+    //              maxRefund, submissionFeeRefund])
+    let mut encoded = hex!("68f87b" "82a4b1" "80" "94").to_vec();
+    encoded.extend_from_slice(sender.as_slice());
+    encoded.extend_from_slice(&hex!("83989680" "830186a0" "94"));
+    encoded.extend_from_slice(ROUTER.as_slice());
+    encoded.extend_from_slice(&hex!("80" "821234" "a0"));
+    encoded.extend_from_slice(ticket_id.as_slice());
+    encoded.push(0x94);
+    encoded.extend_from_slice(Address::repeat_byte(0xfe).as_slice());
+    encoded.extend_from_slice(&hex!("8703854f2af8f000" "823728"));
+    assert_eq!(tx.auto_redeem_hash(U256::from(10_000_000)), keccak256(encoded));
+
+    let creation = SubmitRetryableTx {
+        retryable: SubmitRetryable { to: None, ..tx.retryable.clone() },
+        ..tx
+    };
+    assert_ne!(creation.ticket_id(), ticket_id);
+}
+
+#[test]
+fn rejects_malformed_retryables() {
+    assert_eq!(
+        decode_submit_retryable(&[0u8; 40]),
+        Err(RetryableError::Truncated { field: "l2CallValue", needed: 32, available: 8 })
+    );
+
+    let mut truncated = payload(ROUTER, U256::from(1), &[1, 2, 3]);
+    truncated.pop();
+    assert_eq!(
+        decode_submit_retryable(&truncated),
+        Err(RetryableError::Truncated { field: "data", needed: 3, available: 2 })
+    );
+
+    let gas = U256::from(u64::MAX) + U256::from(1);
+    assert_eq!(
+        decode_submit_retryable(&payload(ROUTER, gas, &[])),
+        Err(RetryableError::GasLimitTooLarge(gas))
+    );
+
+    let mut oversized = payload(ROUTER, U256::from(1), &[]);
+    let len_offset = oversized.len() - 32;
+    oversized[len_offset..].copy_from_slice(&word(U256::from(128 * 1024 + 1)));
+    assert_eq!(
+        decode_submit_retryable(&oversized),
+        Err(RetryableError::DataTooLarge(U256::from(128 * 1024 + 1)))
+    );
+}
@@ -0,0 +1,58 @@
+use alloy::{
+    primitives::{Address, Bytes, U256},
+    rpc::types::Log,
+    sol_types::{SolCall, SolEvent},
+};
+use indexer_rs::sequencer_inbox::{decode_batch_calldata, find_batch_data_event, BatchDataError, SequencerInbox};
+
+#[test]
+fn decodes_batch_data_from_calldata() {
+    let data = Bytes::from_static(&[0x00, 0x1b, 0x2c]);
+    let input = SequencerInbox::addSequencerL2BatchFromOrigin_1Call {
+        sequenceNumber: U256::from(42),
+        data: data.clone(),
+        afterDelayedMessagesRead: U256::from(7),
+        gasRefunder: Address::ZERO,
+        prevMessageCount: U256::from(100),
+        newMessageCount: U256::from(120),
+    }
+    .abi_encode();
+    assert_eq!(decode_batch_calldata(&input).unwrap(), (U256::from(42), data));
+
+    let blob_call = SequencerInbox::addSequencerL2BatchFromBlobsCall {
+        sequenceNumber: U256::from(42),
+        afterDelayedMessagesRead: U256::from(7),
+        gasRefunder: Address::ZERO,
+        prevMessageCount: U256::from(100),
+        newMessageCount: U256::from(120),
+    }
+    .abi_encode();
+    assert!(matches!(decode_batch_calldata(&blob_call), Err(BatchDataError::UnsupportedCall(_))));
+}
+
+#[test]
+fn finds_batch_data_event_by_sequence_number() {
+    let inbox = Address::repeat_byte(0x1c);
+    let log_for = |address: Address, seq: u64, data: &'static [u8]| Log {
+        inner: alloy::primitives::Log {
+            address,
+            data: SequencerInbox::SequencerBatchData {
+                batchSequenceNumber: U256::from(seq),
+                data: Bytes::from_static(data),
+            }
+            .encode_log_data(),
+        },
+        ..Default::default()
+    };
+    let logs = [
+        log_for(Address::repeat_byte(0xee), 5, b"spoofed"),
+        log_for(inbox, 4, b"previous"),
+        log_for(inbox, 5, b"batch"),
+    ];
+
+    assert_eq!(find_batch_data_event(&logs, inbox, U256::from(5)).unwrap(), Bytes::from_static(b"batch"));
+    assert!(matches!(
+        find_batch_data_event(&logs, inbox, U256::from(6)),
+        Err(BatchDataError::MissingBatchDataEvent(_))
+    ));
+}
@@ -0,0 +1,82 @@
+use alloy::primitives::{address, b256, keccak256, Address};
+use indexer_rs::uniswap::path::{check_fee, Hop, PathError, PoolFactory, V3Path, UNISWAP_V3_FACTORY};
+
+const WETH: Address = address!("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1");
+const USDC: Address = address!("0xaf88d065e77c8cC2239327C5EDb3A432268e5831");
+const USDC_E: Address = address!("0xFF970A61A04b1cA14834A43f5dE4533eBDDB5CC8");
+const ARB: Address = address!("0x912CE59144191C1204E64559FE8253a0e49E6548");
+
+#[test]
+fn derives_deployed_pool_addresses() {
+    // WETH/USDC and WETH/USDC.e 0.05% pools on Arbitrum One, and USDC/WETH 0.05% on Ethereum.
+    assert_eq!(
+        UNISWAP_V3_FACTORY.pool_address(WETH, USDC, 500),
+        address!("0xC6962004f452bE9203591991D15f6b388e09E8D0")
+    );
+    assert_eq!(
+        UNISWAP_V3_FACTORY.pool_address(USDC, WETH, 500),
+        address!("0xC6962004f452bE9203591991D15f6b388e09E8D0")
+    );
+    assert_eq!(
+        UNISWAP_V3_FACTORY.pool_address(USDC_E, WETH, 500),
+        address!("0xC31E54c7a869B9FcBEcc14363CF510d1c41fa443")
+    );
+    assert_eq!(
+        UNISWAP_V3_FACTORY.pool_address(
+            address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+            500
+        ),
+        address!("0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640")
+    );
+    assert_ne!(UNISWAP_V3_FACTORY.pool_address(WETH, USDC, 3000), UNISWAP_V3_FACTORY.pool_address(WETH, USDC, 500));
+
+    // A fork's pools follow from its own factory and pool creation code.
+    let fork = PoolFactory {
+        address: address!("0x00000000000000000000000000000000000f0f0f"),
+        init_code_hash: b256!("0x00000000000000000000000000000000000000000000000000000000000f0f0f"),
+    };
+    let salt =
+        keccak256([[0u8; 12].as_slice(), WETH.as_slice(), &[0; 12], USDC.as_slice(), &[0; 30], &[0x01, 0xf4]].concat());
+    assert_eq!(fork.pool_address(USDC, WETH, 500), fork.address.create2(salt, fork.init_code_hash));
+    assert_ne!(
+        fork.pool_address(WETH, USDC, 500),
+        PoolFactory { init_code_hash: UNISWAP_V3_FACTORY.init_code_hash, ..fork }.pool_address(WETH, USDC, 500)
+    );
+}
+
+#[test]
+fn splits_paths_into_hops() {
+    let path = V3Path { tokens: vec![ARB, WETH, USDC], fees: vec![3000, 500] };
+    let encoded = path.encode().unwrap();
+    assert_eq!(encoded.len(), 20 + 2 * 23);
+    assert_eq!(V3Path::decode(&encoded).unwrap(), path);
+
+    let hops = path.hops(&UNISWAP_V3_FACTORY);
+    assert_eq!(hops, [Hop::new(ARB, WETH, 3000, &UNISWAP_V3_FACTORY), Hop::new(WETH, USDC, 500, &UNISWAP_V3_FACTORY)]);
+    assert_eq!(hops[1].pool, address!("0xC6962004f452bE9203591991D15f6b388e09E8D0"));
+
+    // An exactOutput path walked in swap order crosses the same pools the other way round.
+    let reversed = V3Path::decode(&encoded).unwrap().reversed().hops(&UNISWAP_V3_FACTORY);
+    assert_eq!((reversed[0].token_in, reversed[0].token_out, reversed[0].pool), (USDC, WETH, hops[1].pool));
+}
+
+#[test]
+fn rejects_malformed_paths() {
+    assert_eq!(V3Path::decode(&[0; 20]), Err(PathError::InvalidLength(20)));
+    assert_eq!(V3Path::decode(&[0; 44]), Err(PathError::InvalidLength(44)));
+
+    let mut encoded = V3Path { tokens: vec![WETH, USDC], fees: vec![500] }.encode().unwrap().to_vec();
+    encoded[20..23].copy_from_slice(&[0, 0x0b, 0xb9]); // 3001
+    assert_eq!(V3Path::decode(&encoded), Err(PathError::InvalidFee(3001)));
+    assert_eq!(check_fee(10000), Ok(10000));
+    assert_eq!(check_fee(0), Err(PathError::InvalidFee(0)));
+
+    // Paths that would not decode are not encoded either.
+    let encode = |tokens: Vec<Address>, fees: Vec<u32>| V3Path { tokens, fees }.encode();
+    assert_eq!(encode(vec![], vec![]), Err(PathError::InvalidHops { tokens: 0, fees: 0 }));
+    assert_eq!(encode(vec![WETH], vec![]), Err(PathError::InvalidHops { tokens: 1, fees: 0 }));
+    assert_eq!(encode(vec![], vec![500]), Err(PathError::InvalidHops { tokens: 0, fees: 1 }));
+    assert_eq!(encode(vec![WETH, USDC, ARB], vec![500]), Err(PathError::InvalidHops { tokens: 3, fees: 1 }));
+    assert_eq!(encode(vec![WETH, USDC], vec![1 << 24]), Err(PathError::InvalidFee(1 << 24)));
+}
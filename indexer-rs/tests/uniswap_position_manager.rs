@@ -0,0 +1,190 @@
+use alloy::{
+    consensus::{SignableTransaction, TxEip1559, TxEnvelope},
+    eips::eip2718::Encodable2718,
+    network::TxSignerSync,
+    primitives::{address, aliases::I24, aliases::U24, Address, Bytes, TxKind, U256},
+    signers::local::PrivateKeySigner,
+    sol_types::SolCall,
+};
+use indexer_rs::{
+    nitro::tx::decode_signed_tx,
+    uniswap::{
+        path::{PathError, UNISWAP_V3_FACTORY},
+        position_manager::{
+            decode_liquidity_actions, decode_position_manager_call, INonfungiblePositionManager as Npm,
+            LiquidityAmounts, LiquidityKind, PositionDecodeError, MAX_MULTICALL_DEPTH, POSITION_MANAGER,
+        },
+    },
+};
+
+const WETH: Address = address!("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1");
+const USDC: Address = address!("0xaf88d065e77c8cC2239327C5EDb3A432268e5831");
+const OWNER: Address = address!("0x00000000000000000000000000000000000a11ce");
+
+fn mint(fee: u32) -> Bytes {
+    Npm::mintCall {
+        params: Npm::MintParams {
+            token0: WETH,
+            token1: USDC,
+            fee: U24::from(fee),
+            tickLower: I24::try_from(-199_020).unwrap(),
+            tickUpper: I24::try_from(-196_200).unwrap(),
+            amount0Desired: U256::from(10u64.pow(18)),
+            amount1Desired: U256::from(3_000_000_000u64),
+            amount0Min: U256::from(99 * 10u64.pow(16)),
+            amount1Min: U256::from(2_970_000_000u64),
+            recipient: OWNER,
+            deadline: U256::from(1_700_000_000),
+        },
+    }
+    .abi_encode()
+    .into()
+}
+
+fn decrease_collect_unwrap_sweep(token_id: U256) -> Bytes {
+    let calls = vec![
+        Npm::decreaseLiquidityCall {
+            params: Npm::DecreaseLiquidityParams {
+                tokenId: token_id,
+                liquidity: 5_000_000_000_000,
+                amount0Min: U256::ZERO,
+                amount1Min: U256::ZERO,
+                deadline: U256::from(1_700_000_600),
+            },
+        }
+        .abi_encode()
+        .into(),
+        Npm::collectCall {
+            params: Npm::CollectParams {
+                tokenId: token_id,
+                recipient: Address::ZERO,
+                amount0Max: u128::MAX,
+                amount1Max: u128::MAX,
+            },
+        }
+        .abi_encode()
+        .into(),
+        Npm::unwrapWETH9Call { amountMinimum: U256::from(1), recipient: OWNER }.abi_encode().into(),
+        Npm::sweepTokenCall { token: USDC, amountMinimum: U256::from(2), recipient: OWNER }.abi_encode().into(),
+        Npm::burnCall { tokenId: token_id }.abi_encode().into(),
+    ];
+    Npm::multicallCall { data: calls }.abi_encode().into()
+}
+
+#[test]
+fn decodes_position_calls() {
+    let [action] = <[_; 1]>::try_from(decode_position_manager_call(&mint(500)).unwrap()).unwrap();
+    assert_eq!((action.kind, action.token_id), (LiquidityKind::Mint, None));
+    let pool = action.pool.unwrap();
+    assert_eq!((pool.token0, pool.token1, pool.fee), (WETH, USDC, 500));
+    assert_eq!(pool.address(&UNISWAP_V3_FACTORY), address!("0xC6962004f452bE9203591991D15f6b388e09E8D0"));
+    assert_eq!(action.tick_range, Some((-199_020, -196_200)));
+    assert_eq!(
+        action.amounts,
+        LiquidityAmounts::Add {
+            amount0_desired: U256::from(10u64.pow(18)),
+            amount1_desired: U256::from(3_000_000_000u64),
+            amount0_min: U256::from(99 * 10u64.pow(16)),
+            amount1_min: U256::from(2_970_000_000u64),
+        }
+    );
+    assert_eq!((action.recipient, action.deadline), (Some(OWNER), Some(U256::from(1_700_000_000))));
+
+    let input = Npm::increaseLiquidityCall {
+        params: Npm::IncreaseLiquidityParams {
+            tokenId: U256::from(4_000_000),
+            amount0Desired: U256::from(5),
+            amount1Desired: U256::from(6),
+            amount0Min: U256::from(4),
+            amount1Min: U256::from(5),
+            deadline: U256::from(1_700_000_000),
+        },
+    }
+    .abi_encode();
+    let [action] = <[_; 1]>::try_from(decode_position_manager_call(&input).unwrap()).unwrap();
+    assert_eq!((action.kind, action.token_id), (LiquidityKind::IncreaseLiquidity, Some(U256::from(4_000_000))));
+    assert_eq!((action.pool, action.tick_range, action.recipient), (None, None, None));
+}
+
+#[test]
+fn decodes_multicalls_with_payment_legs() {
+    let token_id = U256::from(4_000_000);
+    // createAndInitializePoolIfNecessary(address,address,uint24,uint160) is skipped.
+    let create_pool: Bytes = [&[0x13, 0xea, 0xd5, 0x62][..], &[0u8; 128]].concat().into();
+    let input = Npm::multicallCall {
+        data: vec![
+            create_pool,
+            mint(3000),
+            Npm::refundETHCall {}.abi_encode().into(),
+            decrease_collect_unwrap_sweep(token_id),
+        ],
+    }
+    .abi_encode();
+    let actions = decode_position_manager_call(&input).unwrap();
+    assert_eq!(
+        actions.iter().map(|a| a.kind).collect::<Vec<_>>(),
+        [
+            LiquidityKind::Mint,
+            LiquidityKind::RefundEth,
+            LiquidityKind::DecreaseLiquidity,
+            LiquidityKind::Collect,
+            LiquidityKind::UnwrapWeth9,
+            LiquidityKind::SweepToken,
+            LiquidityKind::Burn,
+        ]
+    );
+    assert_eq!(
+        actions[2].amounts,
+        LiquidityAmounts::Remove { liquidity: 5_000_000_000_000, amount0_min: U256::ZERO, amount1_min: U256::ZERO }
+    );
+    assert_eq!(actions[3].recipient, Some(Address::ZERO));
+    assert_eq!(actions[3].amounts, LiquidityAmounts::Collect { amount0_max: u128::MAX, amount1_max: u128::MAX });
+    assert_eq!(actions[4].amounts, LiquidityAmounts::Sweep { token: None, amount_minimum: U256::from(1) });
+    assert_eq!(actions[5].amounts, LiquidityAmounts::Sweep { token: Some(USDC), amount_minimum: U256::from(2) });
+    assert!(actions[2..].iter().filter(|a| a.token_id.is_some()).all(|a| a.token_id == Some(token_id)));
+}
+
+#[test]
+fn attaches_actions_to_their_l2_transaction() {
+    let signer = PrivateKeySigner::random();
+    let sign = |to: Address, input: Bytes| {
+        let mut tx = TxEip1559 {
+            chain_id: 42161,
+            nonce: 9,
+            gas_limit: 700_000,
+            max_fee_per_gas: 100_000_000,
+            max_priority_fee_per_gas: 0,
+            to: TxKind::Call(to),
+            value: U256::ZERO,
+            access_list: Default::default(),
+            input,
+        };
+        let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+        decode_signed_tx(&TxEnvelope::from(tx.into_signed(signature)).encoded_2718()).unwrap()
+    };
+
+    let tx = sign(POSITION_MANAGER, decrease_collect_unwrap_sweep(U256::from(7)));
+    let actions = decode_liquidity_actions(&tx).unwrap();
+    assert_eq!(actions.len(), 5);
+    assert!(actions.iter().all(|a| a.tx_hash == tx.hash && a.sender == signer.address()));
+    assert_eq!(decode_liquidity_actions(&sign(USDC, mint(500))).unwrap(), []);
+
+    assert!(matches!(
+        decode_position_manager_call(&mint(2500)),
+        Err(PositionDecodeError::Pool(PathError::InvalidFee(2500)))
+    ));
+    assert!(matches!(
+        decode_position_manager_call(&[0xa9, 0x05, 0x9c, 0xbb]),
+        Err(PositionDecodeError::UnknownSelector(_))
+    ));
+
+    // A mint wrapped in more multicalls than are followed is refused, not dropped.
+    let nest = |depth: usize| {
+        (0..depth).fold(mint(500), |input, _| Npm::multicallCall { data: vec![input] }.abi_encode().into())
+    };
+    assert_eq!(decode_position_manager_call(&nest(MAX_MULTICALL_DEPTH)).unwrap().len(), 1);
+    assert!(matches!(
+        decode_position_manager_call(&nest(MAX_MULTICALL_DEPTH + 1)),
+        Err(PositionDecodeError::MulticallTooDeep)
+    ));
+}
@@ -0,0 +1,212 @@
+use alloy::{
+    consensus::{SignableTransaction, TxEip1559, TxEnvelope},
+    eips::eip2718::Encodable2718,
+    network::TxSignerSync,
+    primitives::{address, aliases::U24, Address, Bytes, TxKind, B256, U160, U256},
+    signers::local::PrivateKeySigner,
+    sol_types::SolCall,
+};
+use indexer_rs::{
+    nitro::tx::decode_signed_tx,
+    uniswap::path::{PathError, UNISWAP_V3_FACTORY},
+    uniswap::router::{
+        decode_router_call, decode_swaps, IMulticall, IMulticallExtended, ISwapRouter, IV3SwapRouter,
+        RouterDecodeError, RouterVersion, SwapAmounts, MAX_MULTICALL_DEPTH, MSG_SENDER, SWAP_ROUTER, SWAP_ROUTER_02,
+    },
+};
+
+const WETH: Address = address!("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1");
+const USDC: Address = address!("0xaf88d065e77c8cC2239327C5EDb3A432268e5831");
+const ARB: Address = address!("0x912CE59144191C1204E64559FE8253a0e49E6548");
+const RECIPIENT: Address = address!("0x00000000000000000000000000000000000a11ce");
+
+fn path(tokens: &[Address], fees: &[u32]) -> Bytes {
+    let mut path = tokens[0].to_vec();
+    for (fee, token) in fees.iter().zip(&tokens[1..]) {
+        path.extend_from_slice(&fee.to_be_bytes()[1..]);
+        path.extend_from_slice(token.as_slice());
+    }
+    path.into()
+}
+
+fn exact_input_02(recipient: Address) -> Bytes {
+    IV3SwapRouter::exactInputCall {
+        params: IV3SwapRouter::ExactInputParams {
+            path: path(&[ARB, WETH, USDC], &[3000, 500]),
+            recipient,
+            amountIn: U256::from(10u64.pow(18)),
+            amountOutMinimum: U256::from(1_000_000),
+        },
+    }
+    .abi_encode()
+    .into()
+}
+
+#[test]
+fn decodes_swap_router_calls() {
+    let input = ISwapRouter::exactInputSingleCall {
+        params: ISwapRouter::ExactInputSingleParams {
+            tokenIn: WETH,
+            tokenOut: USDC,
+            fee: U24::from(500),
+            recipient: RECIPIENT,
+            deadline: U256::from(1_700_000_000),
+            amountIn: U256::from(10u64.pow(17)),
+            amountOutMinimum: U256::from(300_000_000),
+            sqrtPriceLimitX96: U160::ZERO,
+        },
+    }
+    .abi_encode();
+    let [swap] = <[_; 1]>::try_from(decode_router_call(&input).unwrap()).unwrap();
+    assert_eq!(swap.router, RouterVersion::SwapRouter);
+    assert_eq!((swap.recipient, swap.path, swap.fees), (RECIPIENT, vec![WETH, USDC], vec![500]));
+    assert_eq!(
+        swap.amounts,
+        SwapAmounts::ExactIn { amount_in: U256::from(10u64.pow(17)), amount_out_minimum: U256::from(300_000_000) }
+    );
+    assert_eq!((swap.sqrt_price_limit_x96, swap.deadline), (Some(U160::ZERO), Some(U256::from(1_700_000_000))));
+
+    // exactOutput paths are encoded from the output token; intents list tokens in swap order.
+    let input = ISwapRouter::exactOutputCall {
+        params: ISwapRouter::ExactOutputParams {
+            path: path(&[USDC, WETH, ARB], &[500, 3000]),
+            recipient: RECIPIENT,
+            deadline: U256::from(1_700_000_000),
+            amountOut: U256::from(50_000_000),
+            amountInMaximum: U256::from(10u128.pow(20)),
+        },
+    }
+    .abi_encode();
+    let [swap] = <[_; 1]>::try_from(decode_router_call(&input).unwrap()).unwrap();
+    assert_eq!(swap.hops(&UNISWAP_V3_FACTORY)[1].pool, address!("0xC6962004f452bE9203591991D15f6b388e09E8D0"));
+    assert_eq!((swap.path, swap.fees), (vec![ARB, WETH, USDC], vec![3000, 500]));
+    assert_eq!(
+        swap.amounts,
+        SwapAmounts::ExactOut { amount_out: U256::from(50_000_000), amount_in_maximum: U256::from(10u128.pow(20)) }
+    );
+    assert_eq!(swap.sqrt_price_limit_x96, None);
+}
+
+#[test]
+fn decodes_swap_router_02_multicalls() {
+    // multicall(uint256 deadline, bytes[]): the deadline applies to every swap inside; helper
+    // calls such as unwrapWETH9(uint256,address) are skipped.
+    let unwrap_weth9: Bytes = [&[0x49, 0x40, 0x4b, 0x7c][..], &[0u8; 64]].concat().into();
+    let exact_output_single = IV3SwapRouter::exactOutputSingleCall {
+        params: IV3SwapRouter::ExactOutputSingleParams {
+            tokenIn: USDC,
+            tokenOut: WETH,
+            fee: U24::from(500),
+            recipient: RECIPIENT,
+            amountOut: U256::from(10u64.pow(16)),
+            amountInMaximum: U256::from(40_000_000),
+            sqrtPriceLimitX96: U160::ZERO,
+        },
+    }
+    .abi_encode();
+    let input = IMulticallExtended::multicall_0Call {
+        deadline: U256::from(1_800_000_000),
+        data: vec![exact_input_02(MSG_SENDER), exact_output_single.clone().into(), unwrap_weth9.clone()],
+    }
+    .abi_encode();
+    let swaps = decode_router_call(&input).unwrap();
+    assert_eq!(swaps.len(), 2);
+    assert!(swaps
+        .iter()
+        .all(|s| s.router == RouterVersion::SwapRouter02 && s.deadline == Some(U256::from(1_800_000_000))));
+    assert_eq!((swaps[0].path.clone(), swaps[0].fees.clone()), (vec![ARB, WETH, USDC], vec![3000, 500]));
+    assert_eq!(swaps[1].path, vec![USDC, WETH]);
+
+    // multicall(bytes[]) and multicall(bytes32 previousBlockhash, bytes[]) carry no deadline.
+    let input = IMulticall::multicallCall { data: vec![exact_output_single.into(), unwrap_weth9] }.abi_encode();
+    let [swap] = <[_; 1]>::try_from(decode_router_call(&input).unwrap()).unwrap();
+    assert_eq!(swap.deadline, None);
+    let input =
+        IMulticallExtended::multicall_1Call { previousBlockhash: B256::ZERO, data: vec![exact_input_02(RECIPIENT)] }
+            .abi_encode();
+    assert_eq!(decode_router_call(&input).unwrap()[0].deadline, None);
+}
+
+#[test]
+fn rejects_calls_that_are_not_router_swaps() {
+    assert!(matches!(decode_router_call(&[0xa9, 0x05, 0x9c, 0xbb, 0]), Err(RouterDecodeError::UnknownSelector(_))));
+    assert!(matches!(decode_router_call(&[0x04]), Err(RouterDecodeError::NoSelector(1))));
+
+    let truncated_path = IV3SwapRouter::exactInputCall {
+        params: IV3SwapRouter::ExactInputParams {
+            path: path(&[ARB, WETH], &[3000])[..30].to_vec().into(),
+            recipient: RECIPIENT,
+            amountIn: U256::from(1),
+            amountOutMinimum: U256::ZERO,
+        },
+    }
+    .abi_encode();
+    assert!(matches!(decode_router_call(&truncated_path), Err(RouterDecodeError::Path(_))));
+
+    let unknown_fee = IV3SwapRouter::exactInputSingleCall {
+        params: IV3SwapRouter::ExactInputSingleParams {
+            tokenIn: WETH,
+            tokenOut: USDC,
+            fee: U24::from(42),
+            recipient: RECIPIENT,
+            amountIn: U256::from(1),
+            amountOutMinimum: U256::ZERO,
+            sqrtPriceLimitX96: U160::ZERO,
+        },
+    }
+    .abi_encode();
+    assert!(matches!(decode_router_call(&unknown_fee), Err(RouterDecodeError::Path(PathError::InvalidFee(42)))));
+
+    // Multicalls nested past the limit are refused, not decoded as carrying no swap.
+    let nest = |depth: usize| {
+        (0..depth).fold(exact_input_02(RECIPIENT), |input, _| {
+            IMulticall::multicallCall { data: vec![input] }.abi_encode().into()
+        })
+    };
+    assert_eq!(decode_router_call(&nest(MAX_MULTICALL_DEPTH)).unwrap().len(), 1);
+    assert!(matches!(decode_router_call(&nest(MAX_MULTICALL_DEPTH + 1)), Err(RouterDecodeError::MulticallTooDeep)));
+}
+
+#[test]
+fn decodes_the_swaps_of_an_l2_transaction() {
+    let signer = PrivateKeySigner::random();
+    let sign = |to: Address, input: Bytes| {
+        let mut tx = TxEip1559 {
+            chain_id: 42161,
+            nonce: 3,
+            gas_limit: 500_000,
+            max_fee_per_gas: 100_000_000,
+            max_priority_fee_per_gas: 0,
+            to: TxKind::Call(to),
+            value: U256::ZERO,
+            access_list: Default::default(),
+            input,
+        };
+        let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+        decode_signed_tx(&TxEnvelope::from(tx.into_signed(signature)).encoded_2718()).unwrap()
+    };
+
+    let tx = sign(SWAP_ROUTER_02, exact_input_02(MSG_SENDER));
+    let [swap] = <[_; 1]>::try_from(decode_swaps(&tx).unwrap()).unwrap();
+    assert_eq!(swap.resolved_recipient(tx.from, SWAP_ROUTER_02), signer.address());
+
+    // SwapRouter keeps the output of swaps to the zero address, e.g. to unwrap it afterwards.
+    let input = ISwapRouter::exactInputCall {
+        params: ISwapRouter::ExactInputParams {
+            path: path(&[ARB, WETH], &[3000]),
+            recipient: Address::ZERO,
+            deadline: U256::from(1_700_000_000),
+            amountIn: U256::from(10u64.pow(18)),
+            amountOutMinimum: U256::from(1),
+        },
+    };
+    let tx = sign(SWAP_ROUTER, input.abi_encode().into());
+    let [swap] = <[_; 1]>::try_from(decode_swaps(&tx).unwrap()).unwrap();
+    assert_eq!(swap.resolved_recipient(tx.from, SWAP_ROUTER), SWAP_ROUTER);
+    let tx = sign(SWAP_ROUTER_02, exact_input_02(Address::ZERO));
+    let [swap] = <[_; 1]>::try_from(decode_swaps(&tx).unwrap()).unwrap();
+    assert_eq!(swap.resolved_recipient(tx.from, SWAP_ROUTER_02), Address::ZERO);
+
+    // The same calldata sent to another contract is not a router swap.
+    assert_eq!(decode_swaps(&sign(USDC, exact_input_02(MSG_SENDER))).unwrap(), []);
+}
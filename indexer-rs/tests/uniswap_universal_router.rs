@@ -0,0 +1,210 @@
+use alloy::{
+    consensus::{SignableTransaction, TxEip1559, TxEnvelope},
+    eips::eip2718::Encodable2718,
+    network::TxSignerSync,
+    primitives::{address, aliases::U48, Address, Bytes, TxKind, U160, U256},
+    signers::local::PrivateKeySigner,
+    sol_types::{SolCall, SolValue},
+};
+use indexer_rs::{
+    nitro::tx::decode_signed_tx,
+    uniswap::{
+        path::PathError,
+        router::{
+            decode_router_call, IMulticallExtended, IV3SwapRouter, RouterVersion, SwapAmounts, SwapIntent, MSG_SENDER,
+        },
+        universal_router::{
+            decode_universal_router_call, decode_universal_router_commands, Command, IAllowanceTransfer,
+            IUniversalRouter, UniversalRouterError, MAX_SUB_PLAN_DEPTH, UNIVERSAL_ROUTERS,
+        },
+    },
+};
+
+const WETH: Address = address!("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1");
+const USDC: Address = address!("0xaf88d065e77c8cC2239327C5EDb3A432268e5831");
+const ARB: Address = address!("0x912CE59144191C1204E64559FE8253a0e49E6548");
+const RECIPIENT: Address = address!("0x00000000000000000000000000000000000a11ce");
+const ADDRESS_THIS: Address = address!("0x0000000000000000000000000000000000000002");
+
+fn path(tokens: &[Address], fees: &[u32]) -> Bytes {
+    let mut path = tokens[0].to_vec();
+    for (fee, token) in fees.iter().zip(&tokens[1..]) {
+        path.extend_from_slice(&fee.to_be_bytes()[1..]);
+        path.extend_from_slice(token.as_slice());
+    }
+    path.into()
+}
+
+fn execute(commands: &[u8], inputs: Vec<Bytes>) -> Bytes {
+    IUniversalRouter::execute_0Call { commands: commands.to_vec().into(), inputs, deadline: U256::from(1_800_000_000) }
+        .abi_encode()
+        .into()
+}
+
+/// Permit2 approval, then ARB -> WETH -> USDC paid by the caller.
+fn permit_and_swap() -> Bytes {
+    let permit = IAllowanceTransfer::PermitSingle {
+        details: IAllowanceTransfer::PermitDetails {
+            token: ARB,
+            amount: U160::MAX,
+            expiration: U48::from(1_800_000_000u64),
+            nonce: U48::from(3),
+        },
+        spender: UNIVERSAL_ROUTERS[0],
+        sigDeadline: U256::from(1_700_001_800),
+    };
+    let swap =
+        (MSG_SENDER, U256::from(10u64.pow(18)), U256::from(1_000_000), path(&[ARB, WETH, USDC], &[3000, 500]), true);
+    execute(
+        &[0x0a, 0x00],
+        vec![(permit, Bytes::from(vec![0x1b; 65])).abi_encode_params().into(), swap.abi_encode_params().into()],
+    )
+}
+
+#[test]
+fn decodes_v3_legs_like_swap_router_02() {
+    let commands = decode_universal_router_call(&permit_and_swap()).unwrap();
+    assert_eq!(commands.len(), 2);
+    assert!(matches!(&commands[0].command, Command::Permit2Permit(permit) if permit.details.token == ARB));
+    let Command::V3Swap { swap, payer_is_user: true } = &commands[1].command else {
+        panic!("expected a V3 swap, got {:?}", commands[1].command);
+    };
+
+    // The same swap through SwapRouter02's multicall(deadline, ...) yields the same intent.
+    let exact_input = IV3SwapRouter::exactInputCall {
+        params: IV3SwapRouter::ExactInputParams {
+            path: path(&[ARB, WETH, USDC], &[3000, 500]),
+            recipient: MSG_SENDER,
+            amountIn: U256::from(10u64.pow(18)),
+            amountOutMinimum: U256::from(1_000_000),
+        },
+    };
+    let multicall = IMulticallExtended::multicall_0Call {
+        deadline: U256::from(1_800_000_000),
+        data: vec![exact_input.abi_encode().into()],
+    };
+    let [expected] = <[_; 1]>::try_from(decode_router_call(&multicall.abi_encode()).unwrap()).unwrap();
+    assert_eq!(swap.router, RouterVersion::UniversalRouter);
+    assert_eq!(*swap, SwapIntent { router: RouterVersion::UniversalRouter, ..expected });
+    assert_eq!(swap.resolved_recipient(RECIPIENT, UNIVERSAL_ROUTERS[0]), RECIPIENT);
+
+    // Exact-output paths are encoded from the output token, as on the SwapRouters.
+    let input = (
+        ADDRESS_THIS,
+        U256::from(50_000_000),
+        U256::from(10u128.pow(20)),
+        path(&[USDC, WETH, ARB], &[500, 3000]),
+        false,
+    );
+    let input =
+        IUniversalRouter::execute_1Call { commands: vec![0x01].into(), inputs: vec![input.abi_encode_params().into()] };
+    let commands = decode_universal_router_call(&input.abi_encode()).unwrap();
+    let [swap] = <[_; 1]>::try_from(commands[0].v3_swaps()).unwrap();
+    assert_eq!((swap.path.clone(), swap.fees.clone(), swap.deadline), (vec![ARB, WETH, USDC], vec![3000, 500], None));
+    assert_eq!(
+        swap.amounts,
+        SwapAmounts::ExactOut { amount_out: U256::from(50_000_000), amount_in_maximum: U256::from(10u128.pow(20)) }
+    );
+    assert_eq!(swap.resolved_recipient(RECIPIENT, UNIVERSAL_ROUTERS[0]), UNIVERSAL_ROUTERS[0]);
+}
+
+#[test]
+fn decodes_payment_commands_and_sub_plans() {
+    // ETH -> WETH, a V2 swap to the router, a 0.25% fee, the rest swept, all inside a sub-plan
+    // that may revert; then the router's WETH is unwrapped.
+    let amount = U256::from(10u64.pow(17));
+    let sub_plan = (
+        Bytes::from(vec![0x08, 0x06, 0x04]),
+        vec![
+            Bytes::from((ADDRESS_THIS, amount, U256::from(1), vec![WETH, USDC], false).abi_encode_params()),
+            Bytes::from((USDC, RECIPIENT, U256::from(25)).abi_encode_params()),
+            Bytes::from((USDC, MSG_SENDER, U256::from(300_000_000)).abi_encode_params()),
+        ],
+    );
+    let input = execute(
+        &[0x0b, 0xa1, 0x0c, 0x13],
+        vec![
+            (ADDRESS_THIS, amount).abi_encode_params().into(),
+            sub_plan.abi_encode_params().into(),
+            (MSG_SENDER, U256::ZERO).abi_encode_params().into(),
+            vec![0xde, 0xad].into(),
+        ],
+    );
+    let commands = decode_universal_router_call(&input).unwrap();
+    assert_eq!(commands[0].command, Command::WrapEth { recipient: ADDRESS_THIS, amount_min: amount });
+    assert_eq!(commands[2].command, Command::UnwrapWeth { recipient: MSG_SENDER, amount_min: U256::ZERO });
+    assert_eq!(commands[3].command, Command::Other { command_type: 0x13, input: vec![0xde, 0xad].into() });
+    assert_eq!(commands.iter().map(|c| c.allow_revert).collect::<Vec<_>>(), [false, true, false, false]);
+
+    let Command::SubPlan(sub_plan) = &commands[1].command else {
+        panic!("expected a sub-plan, got {:?}", commands[1].command);
+    };
+    assert_eq!(
+        sub_plan[0].command,
+        Command::V2Swap {
+            recipient: ADDRESS_THIS,
+            path: vec![WETH, USDC],
+            amounts: SwapAmounts::ExactIn { amount_in: amount, amount_out_minimum: U256::from(1) },
+            payer_is_user: false,
+        }
+    );
+    assert_eq!(sub_plan[1].command, Command::PayPortion { token: USDC, recipient: RECIPIENT, bips: U256::from(25) });
+    assert_eq!(
+        sub_plan[2].command,
+        Command::Sweep { token: USDC, recipient: MSG_SENDER, amount_min: U256::from(300_000_000) }
+    );
+    assert!(commands[1].v3_swaps().is_empty());
+}
+
+#[test]
+fn rejects_malformed_programs_and_filters_transactions() {
+    let input = execute(&[0x0b, 0x0c], vec![(MSG_SENDER, U256::ZERO).abi_encode_params().into()]);
+    assert!(matches!(
+        decode_universal_router_call(&input),
+        Err(UniversalRouterError::LengthMismatch { commands: 2, inputs: 1 })
+    ));
+    let bad_fee = (MSG_SENDER, U256::from(1), U256::ZERO, path(&[WETH, USDC], &[42]), true);
+    assert!(matches!(
+        decode_universal_router_call(&execute(&[0x00], vec![bad_fee.abi_encode_params().into()])),
+        Err(UniversalRouterError::Path(PathError::InvalidFee(42)))
+    ));
+    assert!(matches!(
+        decode_universal_router_call(&[0xa9, 0x05, 0x9c, 0xbb]),
+        Err(UniversalRouterError::UnknownSelector(_))
+    ));
+
+    // Sub-plans nested past the limit are refused, not decoded as empty.
+    let nest = |depth: usize| {
+        let (commands, inputs) = (0..depth).fold((vec![0x3f], vec![Bytes::new()]), |(commands, inputs), _| {
+            (vec![0x21], vec![(Bytes::from(commands), inputs).abi_encode_params().into()])
+        });
+        execute(&commands, inputs)
+    };
+    assert!(decode_universal_router_call(&nest(MAX_SUB_PLAN_DEPTH)).is_ok());
+    assert!(matches!(
+        decode_universal_router_call(&nest(MAX_SUB_PLAN_DEPTH + 1)),
+        Err(UniversalRouterError::SubPlanTooDeep)
+    ));
+
+    let signer = PrivateKeySigner::random();
+    let sign = |to: Address, input: Bytes| {
+        let mut tx = TxEip1559 {
+            chain_id: 42161,
+            nonce: 5,
+            gas_limit: 600_000,
+            max_fee_per_gas: 100_000_000,
+            max_priority_fee_per_gas: 0,
+            to: TxKind::Call(to),
+            value: U256::ZERO,
+            access_list: Default::default(),
+            input,
+        };
+        let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+        decode_signed_tx(&TxEnvelope::from(tx.into_signed(signature)).encoded_2718()).unwrap()
+    };
+    let tx = sign(UNIVERSAL_ROUTERS[1], permit_and_swap());
+    let commands = decode_universal_router_commands(&tx).unwrap();
+    let [swap] = <[_; 1]>::try_from(commands[1].v3_swaps()).unwrap();
+    assert_eq!(swap.resolved_recipient(tx.from, UNIVERSAL_ROUTERS[1]), signer.address());
+    assert_eq!(decode_universal_router_commands(&sign(USDC, permit_and_swap())).unwrap(), []);
+}